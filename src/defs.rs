@@ -8,3 +8,6 @@ pub const TAB: char = '\t';
 
 /// Empty character (zero).
 pub const NULL: char = 0 as char;
+
+/// Byte order mark character.
+pub const BOM: char = '\u{FEFF}';
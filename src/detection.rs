@@ -0,0 +1,102 @@
+//! # Indentation detection
+
+use crate::defs::*;
+use crate::errors::*;
+use crate::options::ParseOptions;
+use crate::position::Span;
+use crate::tokenizer::{self, Token, Tokenizer};
+use std::collections::BTreeMap;
+
+/// Detects the indentation unit and indentation character from all indented lines of input text.
+///
+/// The unit is the most common step between the indentation of consecutive nodes,
+/// the smaller step is chosen when steps are equally common.
+/// When some node is indented by a width that is not a multiple of the most common step,
+/// the unit is the greatest common divisor of all indentation widths, provided that
+/// the indentation increases or decreases by exactly this step at least once.
+/// Otherwise the indentation is inconsistent.
+/// Returns `None` when no node is indented.
+///
+/// ```
+/// use idml::{detect_indent, WS};
+///
+/// assert_eq!(Some((2, WS)), detect_indent(".A\n    .B\n      .C\n    .D\n  .E\n").unwrap());
+/// assert_eq!(None, detect_indent(".A\n.B\n").unwrap());
+/// ```
+pub fn detect_indent(input: &str) -> Result<Option<(usize, char)>> {
+  detect_indent_with_options(input, &ParseOptions::default())
+}
+
+/// Detects the indentation unit and indentation character from all indented lines of input text,
+/// with the specified options. When the tab stop is set, the unit is the width in columns.
+pub fn detect_indent_with_options(input: &str, options: &ParseOptions) -> Result<Option<(usize, char)>> {
+  detect(input, options).map(|detected| detected.map(|(unit, indent_char, _)| (unit, indent_char)))
+}
+
+/// Detects the indentation unit, indentation character and the span of the indentation establishing the unit.
+/// Errors reported by the tokenizer are skipped, they are reported when parsing the input.
+pub(crate) fn detect(input: &str, options: &ParseOptions) -> Result<Option<(usize, char, Span)>> {
+  let (tokens, _) = Tokenizer::with_options(input, options).tokenize_recovering();
+  let mut indent_char = NULL;
+  let mut widths = vec![];
+  let mut steps = vec![];
+  let mut decreasing_steps = vec![];
+  let mut previous = 0;
+  for token in tokens {
+    if let Token::Indentation(indentation, span) = token {
+      let width = options.indentation_width(&indentation);
+      let ch = tokenizer::indent_char(&indentation);
+      if width > 0 {
+        if indent_char == NULL {
+          indent_char = ch;
+        } else if ch != indent_char && options.tab_stop().is_none() {
+          return Err(err_inconsistent_indentation(span.start()));
+        }
+        widths.push((width, span));
+      }
+      if width > previous {
+        steps.push((width - previous, span));
+      } else if width < previous {
+        decreasing_steps.push((previous - width, span));
+      }
+      previous = width;
+    }
+  }
+  let mut counts = BTreeMap::new();
+  for (step, _) in &steps {
+    *counts.entry(*step).or_insert(0_usize) += 1;
+  }
+  // Steps are iterated in ascending order, so the smaller one wins a tie.
+  let Some(common) = counts.iter().fold(None, |best: Option<(usize, usize)>, (step, count)| match best {
+    Some((_, best_count)) if best_count >= *count => best,
+    _ => Some((*step, *count)),
+  }) else {
+    return Ok(None);
+  };
+  let common = common.0;
+  let span_of = |unit: usize| {
+    widths
+      .iter()
+      .chain(steps.iter())
+      .find(|(width, _)| *width == unit)
+      .map_or(Span::default(), |(_, span)| *span)
+  };
+  let Some((malformed, malformed_span)) = widths.iter().find(|(width, _)| !width.is_multiple_of(common)) else {
+    return Ok(Some((common, indent_char, span_of(common))));
+  };
+  let divisor = widths.iter().fold(0, |divisor, (width, _)| gcd(divisor, *width));
+  // Every width is a multiple of the divisor, which is accepted when the indentation changes by exactly this step.
+  if steps.iter().chain(decreasing_steps.iter()).any(|(step, _)| *step == divisor) {
+    return Ok(Some((divisor, indent_char, span_of(divisor))));
+  }
+  Err(err_malformed_indentation(*malformed, common, span_of(common), malformed_span.start()))
+}
+
+/// Returns the greatest common divisor.
+fn gcd(a: usize, b: usize) -> usize {
+  if b == 0 {
+    a
+  } else {
+    gcd(b, a % b)
+  }
+}
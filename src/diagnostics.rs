@@ -0,0 +1,217 @@
+//! # Diagnostics rendering
+
+use crate::defs::*;
+use crate::errors::{ErrorKind, IdmlError};
+use crate::position::{Position, Span};
+use std::fmt::Write;
+
+const ANSI_RESET: &str = "\x1b[0m";
+const ANSI_BOLD: &str = "\x1b[1m";
+const ANSI_ERROR: &str = "\x1b[1;31m";
+const ANSI_NOTE: &str = "\x1b[1;34m";
+const ANSI_HINT: &str = "\x1b[1;36m";
+
+/// Style of the rendered diagnostic report.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Style {
+  /// Plain text, without any control sequences.
+  Plain,
+  /// Text colored using ANSI escape sequences.
+  Ansi,
+}
+
+/// Label attached to a span of the source text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Label {
+  /// Span of the source text the label points to.
+  span: Span,
+  /// Label text displayed next to the underline, may be empty.
+  text: String,
+  /// Flag indicating if this is the primary label of the diagnostic.
+  primary: bool,
+}
+
+/// Diagnostic report pointing to a span of the source text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+  /// The message of the diagnostic.
+  message: String,
+  /// Labels pointing to spans of the source text, the first one is the primary label.
+  labels: Vec<Label>,
+  /// Optional hint displayed at the end of the report.
+  hint: Option<String>,
+}
+
+impl From<&IdmlError> for Diagnostic {
+  /// Creates a [Diagnostic] reporting the specified [IdmlError].
+  fn from(err: &IdmlError) -> Self {
+    let position = err.position();
+    let (columns, bytes) = match err.kind() {
+      ErrorKind::MalformedIndentation { indent, .. } => (*indent, *indent),
+      ErrorKind::UnexpectedCharacter(ch) => (1, ch.len_utf8()),
+      _ => (1, 0),
+    };
+    let end = Position::new(position.row(), position.column() + columns, position.offset() + bytes);
+    let diagnostic = Diagnostic::new(err.kind().to_string(), Span::new(position, end));
+    match err.kind() {
+      ErrorKind::EmptyInput => diagnostic.with_hint("the document must contain at least one node"),
+      ErrorKind::UnexpectedEnd => diagnostic.with_hint("the last line of the document must end with a newline"),
+      ErrorKind::UnexpectedDelimiter(_) => diagnostic.with_hint("the document must begin with one of the allowed delimiters"),
+      ErrorKind::MalformedIndentation {
+        indent,
+        multiplier,
+        multiplier_span,
+      } if *multiplier_span == Span::default() => diagnostic.with_hint(format!("indentation {indent} is not a multiple of {multiplier} set in options")),
+      ErrorKind::MalformedIndentation {
+        indent,
+        multiplier,
+        multiplier_span,
+      } => diagnostic.with_note(*multiplier_span, format!("indentation {multiplier} set here")).with_hint(format!(
+        "indentation {indent} is not a multiple of {multiplier} set on line {}",
+        multiplier_span.start().row()
+      )),
+      ErrorKind::LevelJump { from, .. } => diagnostic.with_hint(format!("the node must be indented at most one level deeper than level {from}")),
+      ErrorKind::InconsistentIndentation => diagnostic.with_hint("use either spaces or tabs for indentation, but not both"),
+      ErrorKind::DepthLimitExceeded(_) | ErrorKind::NodeLimitExceeded(_) | ErrorKind::NameTooLong(_) | ErrorKind::ContentTooLong(_) | ErrorKind::InputTooLong(_) => {
+        diagnostic.with_hint("the limit is set in parsing options")
+      }
+      _ => diagnostic,
+    }
+  }
+}
+
+impl Diagnostic {
+  /// Creates a new diagnostic with the specified message, pointing to the specified span.
+  pub fn new(message: impl Into<String>, span: Span) -> Self {
+    Self {
+      message: message.into(),
+      labels: vec![Label {
+        span,
+        text: "".to_string(),
+        primary: true,
+      }],
+      hint: None,
+    }
+  }
+
+  /// Sets the text displayed next to the underline of the primary span.
+  pub fn with_label(mut self, text: impl Into<String>) -> Self {
+    self.labels[0].text = text.into();
+    self
+  }
+
+  /// Adds a secondary note pointing to the specified span.
+  pub fn with_note(mut self, span: Span, text: impl Into<String>) -> Self {
+    self.labels.push(Label {
+      span,
+      text: text.into(),
+      primary: false,
+    });
+    self
+  }
+
+  /// Sets the hint displayed at the end of the report.
+  pub fn with_hint(mut self, hint: impl Into<String>) -> Self {
+    self.hint = Some(hint.into());
+    self
+  }
+
+  /// Returns the message of the diagnostic.
+  pub fn message(&self) -> &str {
+    &self.message
+  }
+
+  /// Returns the primary span of the diagnostic.
+  pub fn span(&self) -> Span {
+    self.labels[0].span
+  }
+
+  /// Returns the hint of the diagnostic.
+  pub fn hint(&self) -> Option<&str> {
+    self.hint.as_deref()
+  }
+
+  /// Renders the report for the specified source text, loaded from the file with specified name.
+  pub fn render(&self, file_name: &str, source: &str, style: Style) -> String {
+    let paint = |color: &str, text: &str| -> String {
+      match style {
+        Style::Plain => text.to_string(),
+        Style::Ansi => format!("{color}{text}{ANSI_RESET}"),
+      }
+    };
+    let mut labels = self.labels.iter().collect::<Vec<&Label>>();
+    labels.sort_by_key(|label| (label.span.start().row(), !label.primary));
+    let max_row = labels.iter().map(|label| label.span.start().row()).max().unwrap_or(1);
+    let width = max_row.to_string().len();
+    let gutter = " ".repeat(width);
+    let bar = paint(ANSI_NOTE, "|");
+    let start = self.span().start();
+    let mut buffer = String::new();
+    let _ = writeln!(&mut buffer, "{}{}", paint(ANSI_ERROR, "error"), paint(ANSI_BOLD, &format!(": {}", self.message)));
+    let _ = writeln!(&mut buffer, "{gutter}{} {file_name}:{}:{}", paint(ANSI_NOTE, "-->"), start.row(), start.column());
+    let _ = writeln!(&mut buffer, "{gutter} {bar}");
+    let mut last_row = 0;
+    for label in labels {
+      let row = label.span.start().row();
+      let line = line_at(source, row);
+      if row != last_row {
+        let _ = writeln!(&mut buffer, "{} {bar} {line}", paint(ANSI_NOTE, &format!("{row:>width$}")));
+        last_row = row;
+      }
+      let (color, mark) = if label.primary { (ANSI_ERROR, '^') } else { (ANSI_NOTE, '-') };
+      let (prefix, length) = underline(line, label.span);
+      let marks = paint(color, &mark.to_string().repeat(length));
+      let text = if label.text.is_empty() {
+        "".to_string()
+      } else {
+        format!(" {}", paint(color, &label.text))
+      };
+      let _ = writeln!(&mut buffer, "{gutter} {bar} {prefix}{marks}{text}");
+    }
+    let _ = writeln!(&mut buffer, "{gutter} {bar}");
+    if let Some(hint) = &self.hint {
+      let _ = writeln!(&mut buffer, "{gutter} {} {hint}", paint(ANSI_HINT, "= hint:"));
+    }
+    buffer
+  }
+}
+
+/// Returns the content of the line with specified row number, without line ending.
+fn line_at(source: &str, row: usize) -> &str {
+  let mut current_row = 1;
+  let mut start = 0;
+  let mut chars = source.char_indices().peekable();
+  while let Some((index, ch)) = chars.next() {
+    if ch == '\n' || ch == '\r' {
+      if current_row == row {
+        return &source[start..index];
+      }
+      if ch == '\r' && chars.peek().is_some_and(|(_, next)| *next == '\n') {
+        chars.next();
+      }
+      current_row += 1;
+      start = chars.peek().map_or(source.len(), |(next, _)| *next);
+    }
+  }
+  if current_row == row {
+    &source[start..]
+  } else {
+    ""
+  }
+}
+
+/// Returns the prefix preceding the underline and the length of the underline
+/// for the specified span starting in the specified line.
+/// Tabs in the prefix are preserved, so the underline is aligned with the line above.
+fn underline(line: &str, span: Span) -> (String, usize) {
+  let start_column = span.start().column().max(1);
+  let line_length = line.chars().count();
+  let mut prefix = line.chars().take(start_column - 1).map(|ch| if ch == TAB { TAB } else { WS }).collect::<String>();
+  prefix.extend(std::iter::repeat_n(WS, (start_column - 1).saturating_sub(line_length)));
+  let length = if span.end().row() == span.start().row() {
+    span.end().column().saturating_sub(start_column)
+  } else {
+    (line_length + 1).saturating_sub(start_column)
+  };
+  (prefix, length.max(1))
+}
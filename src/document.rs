@@ -0,0 +1,160 @@
+//! # Document implementation
+
+use crate::node::Node;
+use std::borrow::Cow;
+use std::ops::{Deref, DerefMut};
+
+/// Line ending style.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LineEnding {
+  /// Line feed `\n`.
+  Lf,
+  /// Carriage return followed by line feed `\r\n`.
+  CrLf,
+  /// Carriage return `\r`.
+  Cr,
+}
+
+impl LineEnding {
+  /// Returns the characters of the line ending.
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      LineEnding::Lf => "\n",
+      LineEnding::CrLf => "\r\n",
+      LineEnding::Cr => "\r",
+    }
+  }
+}
+
+/// Parsed document.
+///
+/// Wraps the root node of the document tree together with the format detected while parsing,
+/// so the document can be reproduced in the original style.
+/// Dereferences to the root node, also mutably, so the tree can be edited in place.
+///
+/// ```
+/// use idml::{parse, LineEnding, WS};
+///
+/// let document = parse(".A a\n  .B b\n").unwrap();
+/// assert_eq!(Some('.'), document.delimiter());
+/// assert_eq!(Some((2, WS)), document.indent());
+/// assert_eq!(Some(LineEnding::Lf), document.line_ending());
+/// assert_eq!("a", document.first_with_name("A").unwrap().text());
+/// ```
+#[derive(Debug, Clone)]
+pub struct Document<'a> {
+  /// The root node of the document tree.
+  root: Node<'a>,
+  /// The parsed input text, not retained when reading from a stream.
+  source: Option<Cow<'a, str>>,
+  /// Indentation unit and indentation character used while parsing.
+  indent: Option<(usize, char)>,
+  /// The first line ending in the document.
+  line_ending: Option<LineEnding>,
+}
+
+impl<'a> Deref for Document<'a> {
+  type Target = Node<'a>;
+
+  /// Dereferences the document to the root node.
+  fn deref(&self) -> &Self::Target {
+    &self.root
+  }
+}
+
+impl DerefMut for Document<'_> {
+  /// Mutably dereferences the document to the root node.
+  fn deref_mut(&mut self) -> &mut Self::Target {
+    &mut self.root
+  }
+}
+
+impl<'a> Document<'a> {
+  /// Creates a new document with the specified root node, parsed input text and indentation used while parsing.
+  pub(crate) fn new(root: Node<'a>, source: Option<&'a str>, indent: Option<(usize, char)>) -> Self {
+    let line_ending = line_ending(&root);
+    Self {
+      root,
+      source: source.map(Cow::Borrowed),
+      indent,
+      line_ending,
+    }
+  }
+
+  /// Returns the root node of the document tree.
+  pub fn root(&self) -> &Node<'a> {
+    &self.root
+  }
+
+  /// Returns the root node of the document tree, allowing modification of the tree.
+  pub fn root_mut(&mut self) -> &mut Node<'a> {
+    &mut self.root
+  }
+
+  /// Converts this document into the root node of the document tree.
+  pub fn into_root(self) -> Node<'a> {
+    self.root
+  }
+
+  /// Returns the parsed input text, `None` when the document was read from a stream.
+  pub fn source(&self) -> Option<&str> {
+    self.source.as_deref()
+  }
+
+  /// Returns the delimiter of the first node, `None` when the document has no nodes.
+  pub fn delimiter(&self) -> Option<char> {
+    self.root.children().next().map(|node| node.delimiter())
+  }
+
+  /// Returns the indentation unit and indentation character, `None` when no node is indented.
+  ///
+  /// This is the indentation used to determine node levels: fixed in options,
+  /// detected from all lines or taken from the first indented line.
+  /// When the tab stop is set, the unit is the width in columns.
+  pub fn indent(&self) -> Option<(usize, char)> {
+    self.indent
+  }
+
+  /// Returns the style of the first line ending in the document, `None` when the document is a single line without line ending.
+  pub fn line_ending(&self) -> Option<LineEnding> {
+    self.line_ending
+  }
+
+  /// Converts this document into a document that owns its text,
+  /// so it no longer borrows from the parsed input.
+  pub fn into_owned(self) -> Document<'static> {
+    Document {
+      root: self.root.into_owned(),
+      source: self.source.map(|source| Cow::Owned(source.into_owned())),
+      indent: self.indent,
+      line_ending: self.line_ending,
+    }
+  }
+}
+
+/// Returns the first line ending found in the source text of the specified node and its descendants.
+pub(crate) fn line_ending(root: &Node) -> Option<LineEnding> {
+  let mut stack = vec![root];
+  while let Some(node) = stack.pop() {
+    let line_ending = if node.is_root() {
+      find_line_ending(node.content())
+    } else {
+      node.raw_comments().chain([node.content()]).find_map(find_line_ending)
+    };
+    if line_ending.is_some() {
+      return line_ending;
+    }
+    stack.extend(node.children().rev());
+  }
+  root.raw_comments().find_map(find_line_ending)
+}
+
+/// Returns the first line ending found in the specified text.
+pub(crate) fn find_line_ending(text: &str) -> Option<LineEnding> {
+  let index = text.find(['\n', '\r'])?;
+  Some(match &text[index..] {
+    rest if rest.starts_with("\r\n") => LineEnding::CrLf,
+    rest if rest.starts_with('\r') => LineEnding::Cr,
+    _ => LineEnding::Lf,
+  })
+}
@@ -1,65 +1,275 @@
 //! # Errors implementation
 
+use crate::position::{Position, Span};
 use std::fmt;
 use std::fmt::Display;
 
 /// Common result type.
 pub type Result<T, E = IdmlError> = std::result::Result<T, E>;
 
+/// Kinds of errors reported while tokenizing and parsing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ErrorKind {
+  /// Input is empty.
+  EmptyInput,
+  /// Unexpected character on input.
+  UnexpectedCharacter(char),
+  /// Unexpected end of input.
+  UnexpectedEnd,
+  /// Delimiter not allowed by parsing options.
+  UnexpectedDelimiter(char),
+  /// Preamble allowed by parsing options without explicitly allowed delimiters.
+  PreambleWithoutDelimiters,
+  /// Expected indentation token.
+  ExpectedIndentation,
+  /// Expected node name token.
+  ExpectedNodeName,
+  /// Expected node content token.
+  ExpectedNodeContent,
+  /// Indentation is not a multiplication of the indentation established in the document.
+  MalformedIndentation {
+    /// Indentation found on input.
+    indent: usize,
+    /// Indentation multiplier established in the document.
+    multiplier: usize,
+    /// Span of the indentation that established the multiplier.
+    multiplier_span: Span,
+  },
+  /// Indentation is deeper by more than one level than the indentation of the preceding node.
+  LevelJump {
+    /// Level of the preceding node.
+    from: usize,
+    /// Level of the node.
+    to: usize,
+  },
+  /// Indentation mixes spaces and tabs.
+  InconsistentIndentation,
+  /// Nesting depth of nodes exceeds the limit set in options.
+  DepthLimitExceeded(usize),
+  /// Number of nodes exceeds the limit set in options.
+  NodeLimitExceeded(usize),
+  /// Length of the node name exceeds the limit set in options.
+  NameTooLong(usize),
+  /// Length of the node content exceeds the limit set in options.
+  ContentTooLong(usize),
+  /// Length of the input exceeds the limit set in options.
+  InputTooLong(usize),
+  /// Input is not a valid UTF-8 sequence.
+  InvalidUtf8,
+  /// Path expression is not valid, with the reason.
+  InvalidPath(String),
+  /// Reading the input failed.
+  Io {
+    /// The kind of the I/O error.
+    kind: std::io::ErrorKind,
+    /// The message of the I/O error.
+    message: String,
+  },
+}
+
+impl Display for ErrorKind {
+  /// Implementation of [Display] trait for [ErrorKind].
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      ErrorKind::EmptyInput => write!(f, "empty input"),
+      ErrorKind::UnexpectedCharacter(ch) => write!(f, "unexpected character: '{ch}' 0x{:02X}", *ch as usize),
+      ErrorKind::UnexpectedEnd => write!(f, "unexpected end of input"),
+      ErrorKind::UnexpectedDelimiter(ch) => write!(f, "unexpected delimiter: '{ch}' 0x{:02X}", *ch as usize),
+      ErrorKind::PreambleWithoutDelimiters => write!(f, "preamble requires explicitly allowed delimiters"),
+      ErrorKind::ExpectedIndentation => write!(f, "expected indentation token"),
+      ErrorKind::ExpectedNodeName => write!(f, "expected node name token"),
+      ErrorKind::ExpectedNodeContent => write!(f, "expected node content token"),
+      ErrorKind::MalformedIndentation { indent, multiplier, .. } => write!(f, "malformed indentation {indent}, expected multiplication of {multiplier}"),
+      ErrorKind::LevelJump { from, to } => write!(f, "indentation jumps from level {from} to {to}"),
+      ErrorKind::InconsistentIndentation => write!(f, "inconsistent indentation, mixed spaces and tabs"),
+      ErrorKind::DepthLimitExceeded(limit) => write!(f, "nesting depth exceeds the limit of {limit}"),
+      ErrorKind::NodeLimitExceeded(limit) => write!(f, "number of nodes exceeds the limit of {limit}"),
+      ErrorKind::NameTooLong(limit) => write!(f, "node name exceeds the limit of {limit} bytes"),
+      ErrorKind::ContentTooLong(limit) => write!(f, "node content exceeds the limit of {limit} bytes"),
+      ErrorKind::InputTooLong(limit) => write!(f, "input exceeds the limit of {limit} bytes"),
+      ErrorKind::InvalidUtf8 => write!(f, "invalid UTF-8 sequence"),
+      ErrorKind::InvalidPath(reason) => write!(f, "invalid path, {reason}"),
+      ErrorKind::Io { message, .. } => write!(f, "I/O error: {message}"),
+    }
+  }
+}
+
 /// Error definition.
-#[derive(Debug, PartialEq, Eq)]
-pub struct IdmlError(String);
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdmlError {
+  /// The kind of the error.
+  kind: ErrorKind,
+  /// Position in the input where the error was detected.
+  position: Position,
+}
 
 impl Display for IdmlError {
   /// Implementation of [Display] trait for [IdmlError].
   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-    write!(f, "{}", self.0)
+    match self.kind {
+      ErrorKind::EmptyInput => write!(f, "{}", self.kind),
+      _ => write!(f, "{} at row {} and column {}", self.kind, self.position.row(), self.position.column()),
+    }
   }
 }
 
+impl std::error::Error for IdmlError {}
+
 impl IdmlError {
-  /// Creates a new [IdmlError] with specified error message.
-  pub fn new(message: &str) -> Self {
-    Self(message.to_string())
+  /// Creates a new [IdmlError] of the specified kind, detected at specified position.
+  pub fn new(kind: ErrorKind, position: Position) -> Self {
+    Self { kind, position }
+  }
+
+  /// Returns the kind of the error.
+  pub fn kind(&self) -> &ErrorKind {
+    &self.kind
+  }
+
+  /// Returns the position in the input where the error was detected.
+  pub fn position(&self) -> Position {
+    self.position
+  }
+
+  /// Returns the row where the error was detected.
+  pub fn row(&self) -> usize {
+    self.position.row()
+  }
+
+  /// Returns the column where the error was detected.
+  pub fn column(&self) -> usize {
+    self.position.column()
+  }
+
+  /// Returns the byte offset where the error was detected.
+  pub fn offset(&self) -> usize {
+    self.position.offset()
+  }
+
+  /// Returns this error with positions relative to the beginning of a text starting at the specified origin,
+  /// converted to positions relative to the beginning of the whole input.
+  pub(crate) fn shifted(self, origin: Position) -> IdmlError {
+    let kind = match self.kind {
+      ErrorKind::MalformedIndentation {
+        indent,
+        multiplier,
+        multiplier_span,
+      } => ErrorKind::MalformedIndentation {
+        indent,
+        multiplier,
+        multiplier_span: multiplier_span.shifted(origin),
+      },
+      other => other,
+    };
+    IdmlError::new(kind, self.position.shifted(origin))
   }
 }
 
 /// Reports an empty input.
-pub fn err_empty_input() -> IdmlError {
-  IdmlError::new("empty input")
+pub fn err_empty_input(position: Position) -> IdmlError {
+  IdmlError::new(ErrorKind::EmptyInput, position)
 }
 
 /// Reports an unexpected character on input.
-pub fn err_unexpected_character(ch: char, row: usize, col: usize) -> IdmlError {
-  IdmlError::new(&format!("unexpected character: '{ch}' 0x{:02X} at row {row} and column {col}", ch as usize))
+pub fn err_unexpected_character(ch: char, position: Position) -> IdmlError {
+  IdmlError::new(ErrorKind::UnexpectedCharacter(ch), position)
 }
 
 /// Reports an unexpected end of input.
-pub fn err_unexpected_end() -> IdmlError {
-  IdmlError::new("unexpected end of input")
+pub fn err_unexpected_end(position: Position) -> IdmlError {
+  IdmlError::new(ErrorKind::UnexpectedEnd, position)
+}
+
+/// Reports a delimiter not allowed by parsing options.
+pub fn err_unexpected_delimiter(ch: char, position: Position) -> IdmlError {
+  IdmlError::new(ErrorKind::UnexpectedDelimiter(ch), position)
+}
+
+/// Reports the preamble allowed by parsing options without explicitly allowed delimiters.
+pub fn err_preamble_without_delimiters(position: Position) -> IdmlError {
+  IdmlError::new(ErrorKind::PreambleWithoutDelimiters, position)
 }
 
 /// Reports expected node name token.
-pub fn err_expected_node_name() -> IdmlError {
-  IdmlError::new("expected node name token")
+pub fn err_expected_node_name(position: Position) -> IdmlError {
+  IdmlError::new(ErrorKind::ExpectedNodeName, position)
 }
 
 /// Reports expected node content token.
-pub fn err_expected_node_content() -> IdmlError {
-  IdmlError::new("expected node content token")
+pub fn err_expected_node_content(position: Position) -> IdmlError {
+  IdmlError::new(ErrorKind::ExpectedNodeContent, position)
 }
 
 /// Reports expected indentation token.
-pub fn err_expected_indentation() -> IdmlError {
-  IdmlError::new("expected indentation token")
+pub fn err_expected_indentation(position: Position) -> IdmlError {
+  IdmlError::new(ErrorKind::ExpectedIndentation, position)
 }
 
 /// Reports malformed indentation.
-pub fn err_malformed_indentation(indent: usize, multiplier: usize) -> IdmlError {
-  IdmlError::new(&format!("malformed indentation {indent}, expected multiplication of {multiplier}"))
+pub fn err_malformed_indentation(indent: usize, multiplier: usize, multiplier_span: Span, position: Position) -> IdmlError {
+  IdmlError::new(
+    ErrorKind::MalformedIndentation {
+      indent,
+      multiplier,
+      multiplier_span,
+    },
+    position,
+  )
+}
+
+/// Reports indentation jumping by more than one level.
+pub fn err_level_jump(from: usize, to: usize, position: Position) -> IdmlError {
+  IdmlError::new(ErrorKind::LevelJump { from, to }, position)
 }
 
 /// Reports inconsistent indentation.
-pub fn err_inconsistent_indentation() -> IdmlError {
-  IdmlError::new("inconsistent indentation, mixed spaces and tabs")
+pub fn err_inconsistent_indentation(position: Position) -> IdmlError {
+  IdmlError::new(ErrorKind::InconsistentIndentation, position)
+}
+
+/// Reports nesting depth exceeding the limit.
+pub fn err_depth_limit_exceeded(limit: usize, position: Position) -> IdmlError {
+  IdmlError::new(ErrorKind::DepthLimitExceeded(limit), position)
+}
+
+/// Reports number of nodes exceeding the limit.
+pub fn err_node_limit_exceeded(limit: usize, position: Position) -> IdmlError {
+  IdmlError::new(ErrorKind::NodeLimitExceeded(limit), position)
+}
+
+/// Reports node name exceeding the length limit.
+pub fn err_name_too_long(limit: usize, position: Position) -> IdmlError {
+  IdmlError::new(ErrorKind::NameTooLong(limit), position)
+}
+
+/// Reports node content exceeding the length limit.
+pub fn err_content_too_long(limit: usize, position: Position) -> IdmlError {
+  IdmlError::new(ErrorKind::ContentTooLong(limit), position)
+}
+
+/// Reports input exceeding the length limit.
+pub fn err_input_too_long(limit: usize, position: Position) -> IdmlError {
+  IdmlError::new(ErrorKind::InputTooLong(limit), position)
+}
+
+/// Reports invalid UTF-8 sequence.
+pub fn err_invalid_utf8(position: Position) -> IdmlError {
+  IdmlError::new(ErrorKind::InvalidUtf8, position)
+}
+
+/// Reports invalid path expression.
+pub fn err_invalid_path(reason: impl Into<String>, position: Position) -> IdmlError {
+  IdmlError::new(ErrorKind::InvalidPath(reason.into()), position)
+}
+
+/// Reports an I/O error.
+pub fn err_io(err: std::io::Error, position: Position) -> IdmlError {
+  IdmlError::new(
+    ErrorKind::Io {
+      kind: err.kind(),
+      message: err.to_string(),
+    },
+    position,
+  )
 }
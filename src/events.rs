@@ -0,0 +1,347 @@
+//! # Event parser implementation
+
+use crate::defs::*;
+use crate::detection::detect;
+use crate::errors::*;
+use crate::options::{LevelCheck, ParseOptions};
+use crate::position::Span;
+use crate::tokenizer::{indent_char, Token, Tokenizer};
+use std::borrow::Cow;
+use std::collections::VecDeque;
+
+/// Returns the event parser for input text.
+pub fn events(input: &str) -> EventParser<'_> {
+  EventParser::new(Tokenizer::new(input))
+}
+
+/// Returns the event parser for input text, with the specified options.
+pub fn events_with_options<'a>(input: &'a str, options: &ParseOptions) -> EventParser<'a> {
+  EventParser::with_options(Tokenizer::with_options(input, options), options).with_detected_indent(input)
+}
+
+/// Parsing events.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event<'a> {
+  /// Beginning of the node.
+  StartNode {
+    /// Indentation level of the node, top level nodes have level `1`.
+    level: usize,
+    /// Original indentation preceding the delimiter, empty when the node is not indented.
+    indentation: Cow<'a, str>,
+    /// Name delimiter.
+    delimiter: char,
+    /// The name of the node.
+    name: Cow<'a, str>,
+    /// Span of the delimiter and the name.
+    span: Span,
+  },
+  /// Content of the most recently started node, with the span covering the content.
+  /// When the content is interrupted by comments, the rest of the content follows the comments.
+  Content(Cow<'a, str>, Span),
+  /// End of the most recently started node, that was not ended yet.
+  EndNode,
+  /// Comment preceding the next started node, with the span covering the comment.
+  /// Comments followed by content interrupt the content of the most recently started node.
+  /// Comments following the last node are reported at the end of the document.
+  Comment(Cow<'a, str>, Span),
+  /// Text preceding the first node or comment, with the span covering the text.
+  /// Includes the byte order mark, leading blank lines and the preamble, reported before any other event.
+  Preamble(Cow<'a, str>, Span),
+}
+
+/// Event parser state.
+enum EventParserState {
+  /// Expected the indentation token.
+  Indentation,
+  /// Expected the node name token.
+  NodeName,
+  /// Expected the node content token.
+  NodeContent,
+}
+
+/// Pull-based event parser.
+///
+/// Yields parsing events one by one, without building the node tree.
+/// Only the levels of currently open nodes are held in memory,
+/// so when the tokens are read from a [StreamTokenizer](crate::StreamTokenizer),
+/// documents of any size can be processed.
+pub struct EventParser<'a> {
+  state: EventParserState,
+  tokens: Box<dyn Iterator<Item = Result<Token<'a>>> + 'a>,
+  events: VecDeque<Event<'a>>,
+  levels: Vec<usize>,
+  first_indent: usize,
+  first_indent_char: char,
+  first_indent_span: Span,
+  last_indentation: Cow<'a, str>,
+  last_indent_span: Span,
+  /// Flag indicating that the content of the most recently started node was interrupted by a comment.
+  interrupted: bool,
+  options: ParseOptions,
+  detection_error: Option<IdmlError>,
+  node_count: usize,
+  recovering: bool,
+  errors: Vec<IdmlError>,
+  done: bool,
+}
+
+impl<'a> Iterator for EventParser<'a> {
+  type Item = Result<Event<'a>>;
+
+  /// Returns the next parsing event.
+  fn next(&mut self) -> Option<Self::Item> {
+    if let Some(err) = self.detection_error.take() {
+      if let Err(err) = self.report(err) {
+        self.done = true;
+        return Some(Err(err));
+      }
+    }
+    loop {
+      if let Some(event) = self.events.pop_front() {
+        return Some(Ok(event));
+      }
+      if self.done {
+        return None;
+      }
+      match self.tokens.next() {
+        Some(Ok(token)) => {
+          if let Err(err) = self.process_token(token) {
+            self.done = true;
+            return Some(Err(err));
+          }
+        }
+        Some(Err(err)) => {
+          self.done = true;
+          return Some(Err(err));
+        }
+        None => {
+          self.done = true;
+          self.events.extend(self.levels.drain(..).map(|_| Event::EndNode));
+        }
+      }
+    }
+  }
+}
+
+impl<'a> EventParser<'a> {
+  /// Creates a new instance of the event parser, processing the specified tokens.
+  pub fn new<I>(tokens: I) -> Self
+  where
+    I: IntoIterator<Item = Result<Token<'a>>>,
+    I::IntoIter: 'a,
+  {
+    Self::with_options(tokens, &ParseOptions::default())
+  }
+
+  /// Creates a new instance of the event parser with the specified options, processing the specified tokens.
+  pub fn with_options<I>(tokens: I, options: &ParseOptions) -> Self
+  where
+    I: IntoIterator<Item = Result<Token<'a>>>,
+    I::IntoIter: 'a,
+  {
+    // Fixed indentation is not set anywhere in the document, its unit is compared with widths in columns.
+    let (first_indent, first_indent_char) = options.indent().map_or((0, NULL), |(indent, ch)| (options.indent_width(indent, ch), ch));
+    Self {
+      state: EventParserState::Indentation,
+      tokens: Box::new(tokens.into_iter()),
+      events: VecDeque::new(),
+      levels: vec![],
+      first_indent,
+      first_indent_char,
+      first_indent_span: Span::default(),
+      last_indentation: Cow::Borrowed(""),
+      last_indent_span: Span::default(),
+      interrupted: false,
+      options: options.clone(),
+      detection_error: None,
+      node_count: 0,
+      recovering: false,
+      errors: vec![],
+      done: false,
+    }
+  }
+
+  /// Sets the indentation unit detected from all lines of the input text,
+  /// when the detection is enabled in options and the fixed indentation is not set.
+  /// Inconsistent indentation found by the detection is reported before any event.
+  pub(crate) fn with_detected_indent(mut self, input: &str) -> Self {
+    if self.options.indent_detection() && self.options.indent().is_none() {
+      match detect(input, &self.options) {
+        Ok(Some((unit, indent_char, span))) => {
+          self.first_indent = unit;
+          self.first_indent_char = indent_char;
+          self.first_indent_span = span;
+        }
+        Ok(None) => {}
+        Err(err) => self.detection_error = Some(err),
+      }
+    }
+    self
+  }
+
+  /// Returns the indentation unit and indentation character used to determine node levels,
+  /// `None` when no node was indented yet.
+  pub(crate) fn indent(&self) -> Option<(usize, char)> {
+    (self.first_indent > 0).then_some((self.first_indent, self.first_indent_char))
+  }
+
+  /// Returns the marker beginning comment lines, if set in options.
+  pub(crate) fn comment_marker(&self) -> Option<&str> {
+    self.options.comment_marker()
+  }
+
+  /// Switches the parser into the mode continuing after errors.
+  pub(crate) fn set_recovering(&mut self) {
+    self.recovering = true;
+  }
+
+  /// Returns errors reported while recovering.
+  pub(crate) fn take_errors(&mut self) -> Vec<IdmlError> {
+    std::mem::take(&mut self.errors)
+  }
+
+  /// Processes a single token.
+  /// When recovering from an unexpected token, parsing resumes from the next indentation token.
+  fn process_token(&mut self, token: Token<'a>) -> Result<()> {
+    let interrupted = std::mem::take(&mut self.interrupted);
+    match token {
+      Token::Comment(comment, span) => {
+        self.interrupted = matches!(self.state, EventParserState::Indentation) && !self.levels.is_empty();
+        self.events.push_back(Event::Comment(comment, span));
+        return Ok(());
+      }
+      Token::NodeContent(content, span) if interrupted => {
+        // The content interrupted by comments continues.
+        self.events.push_back(Event::Content(content, span));
+        return Ok(());
+      }
+      Token::Preamble(preamble, span) => {
+        self.events.push_back(Event::Preamble(preamble, span));
+        return Ok(());
+      }
+      _ => {}
+    }
+    match self.state {
+      EventParserState::Indentation => {
+        if let Token::Indentation(indentation, span) = token {
+          let width = self.width(&indentation);
+          if self.first_indent == 0 && width > 0 {
+            self.first_indent = width;
+            self.first_indent_char = indent_char(&indentation);
+            self.first_indent_span = span;
+          }
+          self.last_indentation = indentation;
+          self.last_indent_span = span;
+          self.state = EventParserState::NodeName;
+        } else {
+          self.report(err_expected_indentation(token.span().start()))?;
+        }
+      }
+      EventParserState::NodeName => {
+        if let Token::NodeName(name, delimiter, span) = token {
+          self.start_node(name, delimiter, span)?;
+          self.state = EventParserState::NodeContent;
+        } else {
+          self.report(err_expected_node_name(token.span().start()))?;
+          self.resume(token)?;
+        }
+      }
+      EventParserState::NodeContent => {
+        if let Token::NodeContent(content, span) = token {
+          self.events.push_back(Event::Content(content, span));
+          self.state = EventParserState::Indentation;
+        } else {
+          self.report(err_expected_node_content(token.span().start()))?;
+          self.resume(token)?;
+        }
+      }
+    }
+    Ok(())
+  }
+
+  /// Resumes parsing after an unexpected token.
+  fn resume(&mut self, token: Token<'a>) -> Result<()> {
+    self.state = EventParserState::Indentation;
+    if matches!(token, Token::Indentation(..)) {
+      self.process_token(token)?;
+    }
+    Ok(())
+  }
+
+  /// Starts a new node with the last parsed indentation and specified name,
+  /// ending all open nodes having the same or deeper level.
+  fn start_node(&mut self, name: Cow<'a, str>, delimiter: char, span: Span) -> Result<()> {
+    let indent = self.width(&self.last_indentation);
+    let last_indent_char = indent_char(&self.last_indentation);
+    let multiplier = self.first_indent;
+    // When recovering from malformed indentation or checking levels leniently, the node is placed at the nearest level.
+    let strict = self.options.level_check() == LevelCheck::Strict;
+    // Indentation using other character than the established one is reported first,
+    // such a line does not tell how the first indented line was indented.
+    let consistent = indent == 0 || last_indent_char == self.first_indent_char || self.options.tab_stop().is_some();
+    if !consistent {
+      if self.options.indent().is_some() {
+        self.report(err_unexpected_character(last_indent_char, self.last_indent_span.start()))?;
+      } else {
+        self.report(err_inconsistent_indentation(self.last_indent_span.start()))?;
+      }
+    }
+    let mut level = indent.checked_div(multiplier).map_or(1, |level| level + 1);
+    if multiplier > 0 && !indent.is_multiple_of(multiplier) {
+      if strict && consistent && indent < multiplier && multiplier.is_multiple_of(indent) && self.first_indent_span != Span::default() {
+        // The first indented line, that established the multiplier, was indented by multiple levels.
+        self.report(err_level_jump(1, multiplier / indent + 1, self.first_indent_span.start()))?;
+      } else if strict {
+        self.report(err_malformed_indentation(indent, multiplier, self.first_indent_span, self.last_indent_span.start()))?;
+      }
+      level = (indent + multiplier / 2) / multiplier + 1;
+    }
+    // The node is at most one level deeper than the preceding node,
+    // when checking levels leniently or recovering, deeper nodes are placed just below the preceding node.
+    let max_level = self.levels.last().map_or(1, |last_level| last_level + 1);
+    if level > max_level {
+      if strict {
+        self.report(err_level_jump(max_level - 1, level, self.last_indent_span.start()))?;
+      }
+      level = max_level;
+    }
+    // Exceeding limits stops parsing also when recovering.
+    if let Some(limit) = self.options.max_depth().filter(|limit| level > *limit) {
+      return Err(err_depth_limit_exceeded(limit, span.start()));
+    }
+    self.node_count += 1;
+    if let Some(limit) = self.options.max_nodes().filter(|limit| self.node_count > *limit) {
+      return Err(err_node_limit_exceeded(limit, span.start()));
+    }
+    while self.levels.last().is_some_and(|last_level| *last_level >= level) {
+      self.levels.pop();
+      self.events.push_back(Event::EndNode);
+    }
+    self.levels.push(level);
+    self.events.push_back(Event::StartNode {
+      level,
+      indentation: std::mem::take(&mut self.last_indentation),
+      delimiter,
+      name,
+      span,
+    });
+    Ok(())
+  }
+
+  /// Returns the width of the indentation in columns.
+  fn width(&self, indentation: &str) -> usize {
+    self.options.indentation_width(indentation)
+  }
+
+  /// Reports an error.
+  /// When recovering, the error is recorded and parsing continues,
+  /// otherwise the error is returned.
+  fn report(&mut self, err: IdmlError) -> Result<()> {
+    if self.recovering {
+      self.errors.push(err);
+      Ok(())
+    } else {
+      Err(err)
+    }
+  }
+}
@@ -5,12 +5,35 @@
 #![deny(rustdoc::missing_crate_level_docs)]
 
 mod defs;
+mod detection;
+mod diagnostics;
+mod document;
 mod errors;
+mod events;
 mod node;
+mod options;
 mod parser;
+mod path;
+mod position;
+mod stream;
 mod tokenizer;
+mod traversal;
+mod tree;
+mod visitor;
 
-pub use defs::{NULL, TAB, WS};
+pub use defs::{BOM, NULL, TAB, WS};
+pub use detection::{detect_indent, detect_indent_with_options};
+pub use diagnostics::{Diagnostic, Style};
+pub use document::{Document, LineEnding};
+pub use errors::{ErrorKind, IdmlError, Result};
+pub use events::{events, events_with_options, Event, EventParser};
 pub use node::Node;
-pub use parser::{parse, Parser};
+pub use options::{LevelCheck, ParseOptions};
+pub use parser::{parse, parse_recovering, parse_recovering_with_options, parse_with_options, Parser};
+pub use path::Path;
+pub use position::{Position, Span};
+pub use stream::{parse_reader, parse_reader_with_options, StreamTokenizer};
 pub use tokenizer::{tokenize, Token, Tokenizer};
+pub use traversal::Cursor;
+pub use tree::{NodeId, Tree, TreeNode};
+pub use visitor::{Visit, Visitor, VisitorMut};
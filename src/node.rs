@@ -1,71 +1,498 @@
 //! # Tree node implementation
 
 use crate::defs::*;
-use std::fmt::Write;
+use crate::document::{find_line_ending, line_ending, LineEnding};
+use crate::errors::{err_unexpected_character, err_unexpected_delimiter, Result};
+use crate::path::Path;
+use crate::position::{Position, Span};
+use crate::tokenizer::{indent_char, is_allowed_char, is_uniform};
+use crate::traversal::{BreadthFirst, Cursor, PostOrder, PreOrder};
+use crate::visitor::{walk, walk_mut, Visitor, VisitorMut};
+use std::borrow::Cow;
+use std::fmt::{self, Write};
+use std::sync::Arc;
 
 const ROOT_LEVEL: usize = 0;
 const ROOT_DELIMITER: char = NULL;
 const ROOT_NAME: &str = "root";
 const ROOT_CONTENT: &str = "";
+pub(crate) const DEFAULT_INDENT: usize = 2;
 
-/// Tree node.
+/// Indentation preceding the delimiter of the node.
 #[derive(Debug, Clone)]
-pub struct Node {
+enum Indentation<'a> {
+  /// Indentation repeating a single character, with the number of characters and the character.
+  Uniform(usize, char),
+  /// Indentation mixing spaces and tabs, accepted when the tab stop is set.
+  Mixed(Cow<'a, str>),
+}
+
+impl<'a> Indentation<'a> {
+  /// No indentation.
+  const NONE: Self = Indentation::Uniform(0, NULL);
+
+  /// Creates the indentation from the parsed indentation text.
+  fn parsed(indentation: Cow<'a, str>) -> Self {
+    if is_uniform(&indentation) {
+      Indentation::Uniform(indentation.chars().count(), indent_char(&indentation))
+    } else {
+      Indentation::Mixed(indentation)
+    }
+  }
+
+  /// Converts this indentation into an indentation that owns its text.
+  fn into_owned(self) -> Indentation<'static> {
+    match self {
+      Indentation::Uniform(indent, indent_char) => Indentation::Uniform(indent, indent_char),
+      Indentation::Mixed(indentation) => Indentation::Mixed(Cow::Owned(indentation.into_owned())),
+    }
+  }
+}
+
+/// Tree node.
+///
+/// The name and the content borrow from the parsed input whenever possible.
+/// Cloning, dropping and serializing nodes is iterative, so deeply nested trees do not overflow the stack.
+///
+/// Nodes can be created and edited, the levels and the indentation of added nodes
+/// are adjusted to the place in the tree, while all other nodes keep their original text.
+/// Spans always refer to the parsed input, added nodes have empty spans.
+///
+/// ```
+/// use idml::Node;
+///
+/// let mut root = Node::root();
+/// let mut company = Node::new('.', "company")?;
+/// company.set_content("Engos");
+/// company.push_child(Node::new('.', "domain")?)?;
+/// root.push_child(company)?;
+/// assert_eq!(".company Engos\n  .domain\n", root.to_source());
+/// # Ok::<(), idml::IdmlError>(())
+/// ```
+pub struct Node<'a> {
   /// Indentation level of the node.
   /// Root note has level `0`. Top level nodes have value `1`.
   level: usize,
+  /// Indentation preceding the delimiter.
+  /// Original indentation as defined in the parsed document.
+  indentation: Indentation<'a>,
   /// Name delimiter.
   /// Original name delimiter as defined in the parsed document.
   delimiter: char,
   /// The name of the node.
   /// Original name as defined in the parsed document but without delimiter.
-  name: String,
+  name: Cow<'a, str>,
   /// The content of the node.
   /// Original content as defined in the parsed document.
-  content: String,
+  content: Cow<'a, str>,
   /// Child nodes.
   /// A list of all child nodes in the document tree.
-  children: Vec<Node>,
+  children: Vec<Node<'a>>,
+  /// Comments preceding the node, for the root node comments following the last node.
+  /// Original comments including indentation and line endings.
+  comments: Vec<Cow<'a, str>>,
+  /// Comments interrupting the content, each with the byte offset in the content where the comment was placed.
+  /// Original comments including indentation and line endings.
+  inner_comments: Vec<(usize, Cow<'a, str>)>,
+  /// Comment marker recognized when the node was parsed.
+  /// Content lines beginning with the marker are escaped when writing the node.
+  comment_marker: Option<Arc<str>>,
+  /// Span of the whole node.
+  /// Covers the delimiter, the name, the content and all descendant nodes.
+  span: Span,
+  /// Span of the delimiter and the name.
+  name_span: Span,
+  /// Span of the content.
+  content_span: Span,
+}
+
+impl Clone for Node<'_> {
+  /// Clones the node and all descendant nodes iteratively.
+  fn clone(&self) -> Self {
+    let mut stack = vec![(self.clone_without_children(), self.children.iter())];
+    loop {
+      let (_, children) = stack.last_mut().unwrap();
+      if let Some(child) = children.next() {
+        stack.push((child.clone_without_children(), child.children.iter()));
+      } else {
+        let (node, _) = stack.pop().unwrap();
+        match stack.last_mut() {
+          Some((parent, _)) => parent.children.push(node),
+          None => return node,
+        }
+      }
+    }
+  }
 }
 
-impl Node {
-  /// Creates a root node.
-  pub(crate) fn root() -> Self {
+impl fmt::Debug for Node<'_> {
+  /// Formats the node with all descendant nodes listed flat in depth-first pre-order,
+  /// so deeply nested trees are formatted without recursion.
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let mut debug = f.debug_struct("Node");
+    self.debug_fields(&mut debug).field("descendants", &DebugDescendants(self)).finish()
+  }
+}
+
+/// Formats the node without child nodes.
+struct DebugNode<'n, 'a>(&'n Node<'a>);
+
+impl fmt::Debug for DebugNode<'_, '_> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let mut debug = f.debug_struct("Node");
+    self.0.debug_fields(&mut debug).finish()
+  }
+}
+
+/// Formats all descendant nodes as a flat list.
+struct DebugDescendants<'n, 'a>(&'n Node<'a>);
+
+impl fmt::Debug for DebugDescendants<'_, '_> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_list().entries(self.0.descendants().map(DebugNode)).finish()
+  }
+}
+
+impl Drop for Node<'_> {
+  /// Drops all descendant nodes iteratively.
+  fn drop(&mut self) {
+    let mut stack = std::mem::take(&mut self.children);
+    while let Some(mut node) = stack.pop() {
+      stack.append(&mut node.children);
+    }
+  }
+}
+
+impl<'a> Node<'a> {
+  /// Creates an empty root node.
+  pub fn root() -> Self {
     Self {
       level: ROOT_LEVEL,
+      indentation: Indentation::NONE,
       delimiter: ROOT_DELIMITER,
-      name: ROOT_NAME.to_string(),
-      content: ROOT_CONTENT.to_string(),
+      name: Cow::Borrowed(ROOT_NAME),
+      content: Cow::Borrowed(ROOT_CONTENT),
       children: vec![],
+      comments: vec![],
+      inner_comments: vec![],
+      comment_marker: None,
+      span: Span::new(Position::new(1, 1, 0), Position::new(1, 1, 0)),
+      name_span: Span::default(),
+      content_span: Span::default(),
     }
   }
 
+  /// Adds the fields of this node, except child nodes, to the debug output.
+  fn debug_fields<'d, 'f, 'b>(&self, debug: &'d mut fmt::DebugStruct<'f, 'b>) -> &'d mut fmt::DebugStruct<'f, 'b> {
+    debug
+      .field("level", &self.level)
+      .field("indentation", &self.indentation)
+      .field("delimiter", &self.delimiter)
+      .field("name", &self.name)
+      .field("content", &self.content)
+      .field("comments", &self.comments)
+      .field("inner_comments", &self.inner_comments)
+      .field("comment_marker", &self.comment_marker)
+      .field("span", &self.span)
+      .field("name_span", &self.name_span)
+      .field("content_span", &self.content_span)
+  }
+
   /// Returns `true` when node is a root.
   pub(crate) fn is_root(&self) -> bool {
-    self.level == ROOT_LEVEL && self.delimiter == ROOT_DELIMITER && self.name == ROOT_NAME && self.content == ROOT_CONTENT
+    self.level == ROOT_LEVEL
+  }
+
+  /// Creates a new top level node with the specified delimiter and name, without content.
+  ///
+  /// Reports an error when the delimiter or the name contains whitespace or control characters,
+  /// or the name begins with the delimiter, so the node would not be parsed back with the same name.
+  /// Positions in reported errors are relative to the beginning of the delimiter.
+  pub fn new(delimiter: char, name: impl Into<Cow<'a, str>>) -> Result<Self> {
+    let name = name.into();
+    check_name(delimiter, &name, None)?;
+    Ok(Self {
+      level: 1,
+      indentation: Indentation::NONE,
+      delimiter,
+      name,
+      content: Cow::Borrowed("\n"),
+      children: vec![],
+      comments: vec![],
+      inner_comments: vec![],
+      comment_marker: None,
+      span: Span::default(),
+      name_span: Span::default(),
+      content_span: Span::default(),
+    })
   }
 
-  /// Creates a new node.
-  pub(crate) fn new(level: usize, delimiter: char, name: String, content: String) -> Self {
+  /// Creates a new parsed node with empty content.
+  pub(crate) fn parsed(level: usize, indentation: Cow<'a, str>, delimiter: char, name: Cow<'a, str>, name_span: Span) -> Self {
     Self {
       level,
+      indentation: Indentation::parsed(indentation),
       delimiter,
       name,
-      content,
+      content: Cow::default(),
       children: vec![],
+      comments: vec![],
+      inner_comments: vec![],
+      comment_marker: None,
+      span: name_span,
+      name_span,
+      content_span: Span::new(name_span.end(), name_span.end()),
     }
   }
 
+  /// Sets the parsed content of the node.
+  /// The span of this node is extended to cover the content.
+  pub(crate) fn set_parsed_content(&mut self, content: Cow<'a, str>, content_span: Span) {
+    self.content = content;
+    self.content_span = content_span;
+    if content_span.end().offset() > self.span.end().offset() {
+      self.span = Span::new(self.span.start(), content_span.end());
+    }
+  }
+
+  /// Continues the parsed content of the node after the comments interrupting it.
+  /// The spans of this node and its content are extended to cover the continued content.
+  pub(crate) fn continue_parsed_content(&mut self, comments: Vec<Cow<'a, str>>, content: Cow<'a, str>, content_span: Span) {
+    let offset = self.content.len();
+    self.inner_comments.extend(comments.into_iter().map(|comment| (offset, comment)));
+    self.content.to_mut().push_str(&content);
+    self.content_span = Span::new(self.content_span.start(), content_span.end());
+    self.span = Span::new(self.span.start(), content_span.end());
+  }
+
+  /// Sets the comment marker recognized when the node was parsed.
+  pub(crate) fn set_comment_marker(&mut self, comment_marker: Option<Arc<str>>) {
+    self.comment_marker = comment_marker;
+  }
+
+  /// Sets the comments attached to the node.
+  pub(crate) fn set_comments(&mut self, comments: Vec<Cow<'a, str>>) {
+    self.comments = comments;
+  }
+
   /// Adds a child node at the end of the children list.
-  pub(crate) fn add_child(&mut self, node: Node) {
+  /// The span of this node is extended to cover the added child.
+  pub(crate) fn add_child(&mut self, node: Node<'a>) {
+    if node.span.end().offset() > self.span.end().offset() {
+      self.span = Span::new(self.span.start(), node.span.end());
+    }
     self.children.push(node);
   }
 
+  /// Sets the name of the node.
+  ///
+  /// Reports an error like [new](Self::new), also when the name begins with the comment marker
+  /// recognized when the node was parsed. The name of the root node is not written and is set as is.
+  pub fn set_name(&mut self, name: impl Into<Cow<'a, str>>) -> Result<()> {
+    let name = name.into();
+    if !self.is_root() {
+      check_name(self.delimiter, &name, self.comment_marker.as_deref())?;
+    }
+    self.name = name;
+    Ok(())
+  }
+
+  /// Sets the content of the node.
+  ///
+  /// Like the parsed content, non-empty content is separated from the name with a space
+  /// and the content ends with a line ending, both are added when missing.
+  /// The added line ending is the one used in the replaced content, a newline when there is none.
+  /// The content of the root node is the text preceding the first node and is set as is,
+  /// only the line ending of the document is added when the text does not end with one.
+  /// Comments interrupting the replaced content are removed.
+  pub fn set_content(&mut self, content: impl Into<Cow<'a, str>>) {
+    let mut content = content.into();
+    if self.is_root() {
+      if !content.trim_start_matches(BOM).is_empty() && !content.ends_with(['\n', '\r']) {
+        let line_ending = line_ending(self).unwrap_or(LineEnding::Lf);
+        content.to_mut().push_str(line_ending.as_str());
+      }
+    } else {
+      if !content.is_empty() && !content.starts_with([WS, TAB, '\n', '\r']) {
+        content = Cow::Owned(format!(" {content}"));
+      }
+      if !content.ends_with(['\n', '\r']) {
+        let line_ending = find_line_ending(&self.content).unwrap_or(LineEnding::Lf);
+        content.to_mut().push_str(line_ending.as_str());
+      }
+    }
+    self.content = content;
+    self.inner_comments.clear();
+  }
+
+  /// Adds a child node at the end of the children list.
+  /// The levels of the added node and all its descendants are adjusted to the place in the tree.
+  /// Reports an error like [insert_child](Self::insert_child).
+  pub fn push_child(&mut self, node: Node<'a>) -> Result<()> {
+    self.insert_child(self.children.len(), node)
+  }
+
+  /// Inserts a child node at the specified position in the children list.
+  /// The levels of the inserted node and all its descendants are adjusted to the place in the tree,
+  /// nodes moved to another level are indented like the nodes around.
+  /// The line ending of the document is added to the content preceding the inserted node
+  /// and to the last content of the inserted subtree, when they do not end with one.
+  ///
+  /// The inserted nodes take the comment marker recognized when this node was parsed.
+  /// Reports an error when the delimiter of the inserted node differs from the delimiter used in the document,
+  /// or the name of an inserted node begins with the comment marker. Positions in reported errors are relative
+  /// to the beginning of the delimiter of the offending node.
+  ///
+  /// # Panics
+  ///
+  /// Panics when the index is greater than the number of child nodes.
+  pub fn insert_child(&mut self, index: usize, mut node: Node<'a>) -> Result<()> {
+    assert!(index <= self.children.len(), "insertion index (is {index}) should be <= len (is {})", self.children.len());
+    let delimiter = if self.is_root() {
+      self.children.first().map(|child| child.delimiter)
+    } else {
+      Some(self.delimiter)
+    };
+    if delimiter.is_some_and(|delimiter| delimiter != node.delimiter) {
+      return Err(err_unexpected_delimiter(node.delimiter, Position::new(1, 1, 0)));
+    }
+    if let Some(comment_marker) = &self.comment_marker {
+      for node in std::iter::once(&node).chain(node.descendants()) {
+        check_name(node.delimiter, &node.name, Some(comment_marker))?;
+      }
+    }
+    let unit = self.indent_unit().or_else(|| node.indent_unit()).unwrap_or((DEFAULT_INDENT, WS));
+    node.relevel(self.level + 1, unit, self.comment_marker.as_ref());
+    let line_ending = line_ending(self).or_else(|| line_ending(&node)).unwrap_or(LineEnding::Lf);
+    node.last_node_mut().end_line(line_ending);
+    match index.checked_sub(1) {
+      Some(preceding) => self.children[preceding].last_node_mut().end_line(line_ending),
+      None => self.end_line(line_ending),
+    }
+    self.children.insert(index, node);
+    Ok(())
+  }
+
+  /// Returns the last node of this subtree in document order, this node when it has no children.
+  fn last_node_mut(&mut self) -> &mut Node<'a> {
+    let mut node = self;
+    while !node.children.is_empty() {
+      node = node.children.last_mut().unwrap();
+    }
+    node
+  }
+
+  /// Adds the line ending to the content of this node when the content does not end with one,
+  /// so a node following the content starts on a new line.
+  /// The content of the root node gets the line ending only when it is not empty.
+  fn end_line(&mut self, line_ending: LineEnding) {
+    let empty = self.is_root() && self.content.trim_start_matches(BOM).is_empty();
+    if !empty && !self.content.ends_with(['\n', '\r']) {
+      self.content.to_mut().push_str(line_ending.as_str());
+    }
+  }
+
+  /// Removes and returns the child node at the specified position in the children list.
+  ///
+  /// # Panics
+  ///
+  /// Panics when the index is out of bounds.
+  pub fn remove_child(&mut self, index: usize) -> Node<'a> {
+    self.children.remove(index)
+  }
+
+  /// Retains only the child nodes for which the specified predicate returns `true`.
+  pub fn retain(&mut self, f: impl FnMut(&Node<'a>) -> bool) {
+    self.children.retain(f);
+  }
+
+  /// Returns an iterator over all child nodes, allowing modification of each node.
+  pub fn children_mut(&mut self) -> impl DoubleEndedIterator<Item = &mut Node<'a>> {
+    self.children.iter_mut()
+  }
+
+  /// Returns the first child node having the specified name, allowing modification of the node.
+  pub fn first_with_name_mut(&mut self, name: impl AsRef<str>) -> Option<&mut Node<'a>> {
+    self.children.iter_mut().find(|node| node.name == name.as_ref())
+  }
+
+  /// Returns the indentation unit and indentation character used by this node or its nearest descendants.
+  fn indent_unit(&self) -> Option<(usize, char)> {
+    let first_child = self.children.first();
+    [Some(self), first_child, first_child.and_then(|child| child.children.first())]
+      .into_iter()
+      .flatten()
+      .find_map(|node| node.own_indent_unit())
+  }
+
+  /// Returns the indentation unit and indentation character used by this node, `None` when the node is not indented.
+  pub(crate) fn own_indent_unit(&self) -> Option<(usize, char)> {
+    match self.indentation {
+      Indentation::Uniform(indent, indent_char) if self.level > 1 && indent > 0 => Some((indent / (self.level - 1), indent_char)),
+      _ => None,
+    }
+  }
+
+  /// Sets the level of this node and adjusts the levels of all descendant nodes.
+  /// Nodes changing the level or not indented yet are indented by the specified unit.
+  /// The comment marker, when specified, is set in all nodes.
+  fn relevel(&mut self, level: usize, unit: (usize, char), comment_marker: Option<&Arc<str>>) {
+    let mut stack = vec![(self, level)];
+    while let Some((node, level)) = stack.pop() {
+      node.set_level(level, unit);
+      if let Some(comment_marker) = comment_marker {
+        node.comment_marker = Some(comment_marker.clone());
+      }
+      stack.extend(node.children.iter_mut().map(|child| (child, level + 1)));
+    }
+  }
+
+  /// Sets the level of this node only, the node is indented by the specified unit
+  /// when it changes the level or is not indented yet.
+  pub(crate) fn set_level(&mut self, level: usize, (unit, indent_char): (usize, char)) {
+    let indented = if level > 1 { self.indent() > 0 } else { self.indent() == 0 };
+    if self.level != level || !indented {
+      self.level = level;
+      self.indentation = if level > 1 {
+        Indentation::Uniform((level - 1) * unit, indent_char)
+      } else {
+        Indentation::NONE
+      };
+    }
+  }
+
+  /// Takes all child nodes out of this node.
+  pub(crate) fn take_children(&mut self) -> Vec<Node<'a>> {
+    std::mem::take(&mut self.children)
+  }
+
+  /// Replaces all child nodes of this node, levels of the child nodes are not adjusted.
+  pub(crate) fn set_children(&mut self, children: Vec<Node<'a>>) {
+    self.children = children;
+  }
+
   /// Returns the indentation level of the node.
   pub fn level(&self) -> usize {
     self.level
   }
 
+  /// Returns the number of characters of the original indentation preceding the delimiter.
+  pub fn indent(&self) -> usize {
+    match &self.indentation {
+      Indentation::Uniform(indent, _) => *indent,
+      Indentation::Mixed(indentation) => indentation.chars().count(),
+    }
+  }
+
+  /// Returns the original indentation character, [NULL] when the node is not indented.
+  /// When the indentation mixes spaces and tabs, this is the first character of the indentation.
+  pub fn indent_char(&self) -> char {
+    match &self.indentation {
+      Indentation::Uniform(_, indent_char) => *indent_char,
+      Indentation::Mixed(indentation) => indent_char(indentation),
+    }
+  }
+
   /// Returns the delimiter of the node.
   pub fn delimiter(&self) -> char {
     self.delimiter
@@ -77,72 +504,353 @@ impl Node {
   }
 
   /// Returns the node content.
+  /// The content of the root node is the original text preceding the first node.
   pub fn content(&self) -> &str {
     &self.content
   }
 
+  /// Returns the preamble, the text preceding the first node without the byte order mark.
+  /// Only the root node has a preamble, for other nodes an empty string is returned.
+  pub fn preamble(&self) -> &str {
+    if self.is_root() {
+      self.content.trim_start_matches(BOM)
+    } else {
+      ""
+    }
+  }
+
+  /// Returns the span of the whole node, including all descendant nodes.
+  pub fn span(&self) -> Span {
+    self.span
+  }
+
+  /// Returns the span of the delimiter and the name of the node.
+  pub fn name_span(&self) -> Span {
+    self.name_span
+  }
+
+  /// Returns the span of the node content.
+  pub fn content_span(&self) -> Span {
+    self.content_span
+  }
+
   /// Returns the node text.
   /// Node text is a trimmed node content.
   pub fn text(&self) -> &str {
     self.content.trim()
   }
 
+  /// Returns an iterator over trimmed comments preceding the node.
+  /// For the root node, returns comments following the last node of the document.
+  pub fn comments(&self) -> impl Iterator<Item = &str> {
+    self.comments.iter().map(|comment| comment.trim())
+  }
+
+  /// Returns an iterator over original comments, including indentation and line endings.
+  pub(crate) fn raw_comments(&self) -> impl Iterator<Item = &str> {
+    self.comments.iter().map(|comment| comment.as_ref())
+  }
+
   /// Returns the first child node having the specified name.
-  pub fn first_with_name(&self, name: impl AsRef<str>) -> Option<&Node> {
+  pub fn first_with_name(&self, name: impl AsRef<str>) -> Option<&Node<'a>> {
     self.children.iter().find(|node| node.name == name.as_ref())
   }
 
   /// Returns the last child node having the specified name.
-  pub fn last_with_name(&self, name: impl AsRef<str>) -> Option<&Node> {
+  pub fn last_with_name(&self, name: impl AsRef<str>) -> Option<&Node<'a>> {
     self.children.iter().rev().find(|node| node.name == name.as_ref())
   }
 
   /// Returns an iterator over all child nodes.
-  pub fn children(&self) -> impl Iterator<Item = &Node> {
+  pub fn children(&self) -> impl DoubleEndedIterator<Item = &Node<'a>> {
     self.children.iter()
   }
 
+  /// Returns the child node at the specified index.
+  pub fn child(&self, index: usize) -> Option<&Node<'a>> {
+    self.children.get(index)
+  }
+
   /// Returns an iterator over child nodes that have the specified name.
-  pub fn with_name(&self, name: impl AsRef<str>) -> impl Iterator<Item = &Node> {
+  pub fn with_name(&self, name: impl AsRef<str>) -> impl Iterator<Item = &Node<'a>> {
     self.children.iter().filter(move |node| node.name == name.as_ref())
   }
 
   /// Returns an iterator over child nodes that have any of the specified names.
-  pub fn with_names<'a>(&'a self, names: &'a [impl AsRef<str>]) -> impl Iterator<Item = &'a Node> {
+  pub fn with_names<'b>(&'b self, names: &'b [impl AsRef<str>]) -> impl Iterator<Item = &'b Node<'a>> {
     let names = names.iter().map(|name| name.as_ref()).collect::<Vec<&str>>();
     self.children.iter().filter(move |node| names.contains(&node.name()))
   }
 
   /// Returns an iterator over child nodes, excluding those with the specified name.
-  pub fn excluding_name(&self, name: impl AsRef<str>) -> impl Iterator<Item = &Node> {
+  pub fn excluding_name(&self, name: impl AsRef<str>) -> impl Iterator<Item = &Node<'a>> {
     self.children.iter().filter(move |node| node.name != name.as_ref())
   }
 
   /// Returns an iterator over child nodes, excluding those with any of the specified names.
-  pub fn excluding_names<'a>(&'a self, names: &'a [impl AsRef<str>]) -> impl Iterator<Item = &'a Node> {
+  pub fn excluding_names<'b>(&'b self, names: &'b [impl AsRef<str>]) -> impl Iterator<Item = &'b Node<'a>> {
     let names = names.iter().map(|name| name.as_ref()).collect::<Vec<&str>>();
     self.children.iter().filter(move |node| !names.contains(&node.name()))
   }
 
+  /// Returns an iterator over all descendant nodes in depth-first pre-order,
+  /// each node is followed by its descendants, this node itself is not included.
+  ///
+  /// ```
+  /// use idml::parse;
+  ///
+  /// let root = parse(".A\n  .B\n.C\n").unwrap();
+  /// let names = root.descendants().map(|node| node.name()).collect::<Vec<&str>>();
+  /// assert_eq!(vec!["A", "B", "C"], names);
+  /// ```
+  pub fn descendants(&self) -> impl Iterator<Item = &Node<'a>> {
+    PreOrder::new(self)
+  }
+
+  /// Returns an iterator over all descendant nodes in depth-first post-order,
+  /// each node is preceded by its descendants, this node itself is not included.
+  pub fn descendants_post_order(&self) -> impl Iterator<Item = &Node<'a>> {
+    PostOrder::new(self)
+  }
+
+  /// Returns an iterator over all descendant nodes in breadth-first order,
+  /// nodes nested deeper follow all nodes nested less deeply, this node itself is not included.
+  pub fn descendants_breadth_first(&self) -> impl Iterator<Item = &Node<'a>> {
+    BreadthFirst::new(self)
+  }
+
+  /// Returns an iterator over descendant nodes that have the specified name, in depth-first pre-order.
+  pub fn descendants_with_name(&self, name: impl AsRef<str>) -> impl Iterator<Item = &Node<'a>> {
+    self.descendants().filter(move |node| node.name == name.as_ref())
+  }
+
+  /// Returns a cursor placed at this node, navigating to parent and sibling nodes of its descendants.
+  pub fn cursor(&self) -> Cursor<'_, 'a> {
+    Cursor::new(self)
+  }
+
+  /// Walks this node and all its descendants in depth-first order with the specified visitor,
+  /// without recursion, so deeply nested trees do not overflow the stack.
+  /// Returns `false` when the walk was stopped by the visitor.
+  pub fn walk(&self, visitor: &mut (impl Visitor + ?Sized)) -> bool {
+    walk(self, visitor)
+  }
+
+  /// Walks this node and all its descendants in depth-first order with the specified visitor,
+  /// allowing modification of visited nodes. Returns `false` when the walk was stopped by the visitor.
+  ///
+  /// ```
+  /// use idml::{parse, Node, Visit, VisitorMut};
+  ///
+  /// struct Redact;
+  ///
+  /// impl VisitorMut for Redact {
+  ///   fn enter_node(&mut self, node: &mut Node) -> Visit {
+  ///     if node.name() == "password" {
+  ///       node.set_content("***");
+  ///     }
+  ///     Visit::Continue
+  ///   }
+  /// }
+  ///
+  /// let mut root = parse(".user\n  .password secret\n").unwrap();
+  /// root.walk_mut(&mut Redact);
+  /// assert_eq!(".user\n  .password ***\n", root.to_source());
+  /// ```
+  pub fn walk_mut(&mut self, visitor: &mut (impl VisitorMut + ?Sized)) -> bool {
+    walk_mut(self, visitor)
+  }
+
+  /// Returns an iterator over nodes selected by the specified path expression, relative to this node.
+  /// See [Path] for the syntax of the expression, compile the path once to select nodes repeatedly.
+  ///
+  /// ```
+  /// use idml::parse;
+  ///
+  /// let root = parse(".domains\n  . analysts\n  . developers\n").unwrap();
+  /// let second = root.select("domains/[1]").unwrap().next().unwrap();
+  /// assert_eq!("developers", second.text());
+  /// ```
+  pub fn select<'b>(&'b self, path: &str) -> Result<impl Iterator<Item = &'b Node<'a>>> {
+    Ok(Path::new(path)?.select(self))
+  }
+
+  /// Returns an iterator over nodes selected by the compiled path, relative to this node.
+  pub fn select_path<'b>(&'b self, path: &Path) -> impl Iterator<Item = &'b Node<'a>> {
+    path.select(self)
+  }
+
   /// Returns the number of child nodes.
   pub fn child_count(&self) -> usize {
     self.children.len()
   }
 
+  /// Converts this node into a node that owns its name and content,
+  /// so it no longer borrows from the parsed input.
+  pub fn into_owned(mut self) -> Node<'static> {
+    let children = std::mem::take(&mut self.children).into_iter();
+    let mut stack = vec![(self.take_owned(), children)];
+    loop {
+      let (_, children) = stack.last_mut().unwrap();
+      if let Some(mut child) = children.next() {
+        let children = std::mem::take(&mut child.children).into_iter();
+        stack.push((child.take_owned(), children));
+      } else {
+        let (node, _) = stack.pop().unwrap();
+        match stack.last_mut() {
+          Some((parent, _)) => parent.children.push(node),
+          None => return node,
+        }
+      }
+    }
+  }
+
+  /// Returns a copy of this node without child nodes.
+  fn clone_without_children(&self) -> Self {
+    Self {
+      level: self.level,
+      indentation: self.indentation.clone(),
+      delimiter: self.delimiter,
+      name: self.name.clone(),
+      content: self.content.clone(),
+      children: vec![],
+      comments: self.comments.clone(),
+      inner_comments: self.inner_comments.clone(),
+      comment_marker: self.comment_marker.clone(),
+      span: self.span,
+      name_span: self.name_span,
+      content_span: self.content_span,
+    }
+  }
+
+  /// Takes the name, the content and the comments out of this node
+  /// and returns them in a node that owns them, without child nodes.
+  fn take_owned(&mut self) -> Node<'static> {
+    Node {
+      level: self.level,
+      indentation: std::mem::replace(&mut self.indentation, Indentation::NONE).into_owned(),
+      delimiter: self.delimiter,
+      name: Cow::Owned(std::mem::take(&mut self.name).into_owned()),
+      content: Cow::Owned(std::mem::take(&mut self.content).into_owned()),
+      children: vec![],
+      comments: self.comments.drain(..).map(|comment| Cow::Owned(comment.into_owned())).collect(),
+      inner_comments: self.inner_comments.drain(..).map(|(offset, comment)| (offset, Cow::Owned(comment.into_owned()))).collect(),
+      comment_marker: self.comment_marker.clone(),
+      span: self.span,
+      name_span: self.name_span,
+      content_span: self.content_span,
+    }
+  }
+
   /// Returns a document starting from this node.
+  ///
+  /// Content lines beginning with the delimiter or the comment marker are escaped with the delimiter,
+  /// such documents are read back with [escaping](crate::ParseOptions::with_escaping) enabled.
   pub fn document(&self, indent: usize, ch: char) -> String {
     let mut buffer = String::new();
-    if !self.is_root() {
-      let indentation = if self.level > 1 {
-        ch.to_string().repeat((self.level - 1) * indent)
+    let mut stack = vec![self];
+    while let Some(node) = stack.pop() {
+      let indentation = if node.level > 1 {
+        ch.to_string().repeat((node.level - 1) * indent)
       } else {
         "".to_string()
       };
-      let _ = write!(&mut buffer, "{}{}{}{}", indentation, self.delimiter, self.name, self.content);
+      if !node.is_root() {
+        for comment in &node.comments {
+          let _ = write!(&mut buffer, "{}{}", indentation, comment.trim_start_matches([WS, TAB]));
+        }
+        let _ = write!(&mut buffer, "{}{}{}", indentation, node.delimiter, node.name);
+        let inner_comments = node
+          .inner_comments
+          .iter()
+          .map(|(offset, comment)| (*offset, Cow::Owned(format!("{}{}", indentation, comment.trim_start_matches([WS, TAB])))));
+        write_content(&mut buffer, &node.content, node.delimiter, node.comment_marker.as_deref(), inner_comments);
+      } else {
+        buffer.push_str(&node.content);
+      }
+      stack.extend(node.children.iter().rev());
     }
-    for child in &self.children {
-      let _ = write!(&mut buffer, "{}", child.document(indent, ch));
+    if self.is_root() {
+      for comment in &self.comments {
+        buffer.push_str(comment.trim_start_matches([WS, TAB]));
+      }
     }
     buffer
   }
+
+  /// Returns the source text of this node and all descendant nodes.
+  ///
+  /// Unlike [document](Self::document), the original indentation, line endings
+  /// and blank lines are preserved, so for the root of a parsed document
+  /// the returned text is byte-exact equal to the parsed input.
+  /// Edited content is escaped like in [document](Self::document).
+  pub fn to_source(&self) -> String {
+    let mut buffer = String::new();
+    self.write_source(&mut buffer);
+    buffer
+  }
+
+  /// Writes the source text of this node and all descendant nodes into the buffer.
+  fn write_source(&self, buffer: &mut String) {
+    let mut stack = vec![self];
+    while let Some(node) = stack.pop() {
+      if !node.is_root() {
+        node.comments.iter().for_each(|comment| buffer.push_str(comment));
+        match &node.indentation {
+          Indentation::Uniform(indent, indent_char) => buffer.extend(std::iter::repeat_n(*indent_char, *indent)),
+          Indentation::Mixed(indentation) => buffer.push_str(indentation),
+        }
+        buffer.push(node.delimiter);
+        buffer.push_str(&node.name);
+        let inner_comments = node.inner_comments.iter().map(|(offset, comment)| (*offset, Cow::Borrowed(comment.as_ref())));
+        write_content(buffer, &node.content, node.delimiter, node.comment_marker.as_deref(), inner_comments);
+      } else {
+        buffer.push_str(&node.content);
+      }
+      stack.extend(node.children.iter().rev());
+    }
+    if self.is_root() {
+      self.comments.iter().for_each(|comment| buffer.push_str(comment));
+    }
+  }
+}
+
+/// Checks that the delimiter followed by the name is parsed back as a node with the same name.
+/// The name must not begin with the delimiter nor the comment marker, such lines escape the node content.
+fn check_name(delimiter: char, name: &str, comment_marker: Option<&str>) -> Result<()> {
+  if !is_allowed_char(delimiter) {
+    return Err(err_unexpected_delimiter(delimiter, Position::new(1, 1, 0)));
+  }
+  let escaping = name.starts_with(delimiter) || comment_marker.is_some_and(|marker| !marker.is_empty() && name.starts_with(marker));
+  for (column, (offset, ch)) in name.char_indices().enumerate() {
+    if !is_allowed_char(ch) || (offset == 0 && escaping) {
+      return Err(err_unexpected_character(ch, Position::new(1, column + 2, offset + delimiter.len_utf8())));
+    }
+  }
+  Ok(())
+}
+
+/// Writes the node content into the buffer, doubling the delimiter
+/// at the beginning of content lines, so they are not parsed as nodes.
+/// The delimiter also precedes the comment marker at the beginning of content lines, so they are not parsed as comments.
+/// Comments interrupting the content are written at their offsets in the content.
+fn write_content<'c>(buffer: &mut String, content: &str, delimiter: char, comment_marker: Option<&str>, comments: impl Iterator<Item = (usize, Cow<'c, str>)>) {
+  let mut comments = comments.peekable();
+  let mut line_start = false;
+  for (offset, ch) in content.char_indices() {
+    while let Some((_, comment)) = comments.next_if(|(comment_offset, _)| *comment_offset <= offset) {
+      buffer.push_str(&comment);
+      line_start = true;
+    }
+    if line_start && (ch == delimiter || comment_marker.is_some_and(|marker| !marker.is_empty() && content[offset..].starts_with(marker))) {
+      buffer.push(delimiter);
+    }
+    if ch == '\n' || ch == '\r' {
+      line_start = true;
+    } else if ch != WS && ch != TAB {
+      line_start = false;
+    }
+    buffer.push(ch);
+  }
+  comments.for_each(|(_, comment)| buffer.push_str(&comment));
 }
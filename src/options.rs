@@ -0,0 +1,316 @@
+//! # Parsing options
+
+use crate::defs::TAB;
+
+/// Checking of indentation levels.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum LevelCheck {
+  /// Indentation that is not a multiple of the indentation unit is reported as an error.
+  /// Indentation deeper by more than one level than the indentation
+  /// of the preceding node is reported as an error.
+  #[default]
+  Strict,
+  /// Indentation that is not a multiple of the indentation unit
+  /// is silently rounded to the nearest level.
+  /// Node indented deeper by more than one level than the preceding node
+  /// is silently placed one level deeper than the preceding node, becoming its child.
+  Lenient,
+}
+
+/// Options controlling the tokenizing and parsing.
+///
+/// Default options accept any document accepted by [parse](crate::parse).
+///
+/// ```
+/// use idml::{parse_with_options, ParseOptions, WS};
+///
+/// let options = ParseOptions::new().with_delimiter('.').with_indent(2, WS);
+/// assert!(parse_with_options(".A\n  .B\n", &options).is_ok());
+/// assert!(parse_with_options("ab\n", &options).is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseOptions {
+  /// Allowed delimiters, any non-whitespace character is allowed when not specified.
+  delimiters: Option<Vec<char>>,
+  /// Fixed indentation unit and indentation character.
+  indent: Option<(usize, char)>,
+  /// Flag indicating if the last line of the document must end with a newline.
+  final_newline_required: bool,
+  /// Checking of indentation levels.
+  level_check: LevelCheck,
+  /// Marker beginning comment lines, comments are not recognized when not specified.
+  comment_marker: Option<String>,
+  /// Flag indicating if content lines beginning with the doubled delimiter or the delimiter followed by the comment marker are escaped.
+  escaping: bool,
+  /// Flag indicating if any text may precede the first node.
+  preamble_allowed: bool,
+  /// Tab stop used to compute the width of indentation mixing spaces and tabs.
+  tab_stop: Option<usize>,
+  /// Flag indicating if the indentation unit is detected from all lines of the document.
+  indent_detection: bool,
+  /// Maximum nesting depth of nodes.
+  max_depth: Option<usize>,
+  /// Maximum number of nodes in the document.
+  max_nodes: Option<usize>,
+  /// Maximum length of the node name in bytes.
+  max_name_len: Option<usize>,
+  /// Maximum length of the node content in bytes.
+  max_content_len: Option<usize>,
+  /// Maximum length of the whole input in bytes.
+  max_input_len: Option<usize>,
+}
+
+impl Default for ParseOptions {
+  /// Creates default parsing options.
+  fn default() -> Self {
+    Self {
+      delimiters: None,
+      indent: None,
+      final_newline_required: false,
+      level_check: LevelCheck::default(),
+      comment_marker: None,
+      escaping: false,
+      preamble_allowed: false,
+      tab_stop: None,
+      indent_detection: false,
+      max_depth: None,
+      max_nodes: None,
+      max_name_len: None,
+      max_content_len: None,
+      max_input_len: None,
+    }
+  }
+}
+
+impl ParseOptions {
+  /// Creates default parsing options.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Sets the only delimiter allowed in the document.
+  pub fn with_delimiter(self, delimiter: char) -> Self {
+    self.with_delimiters([delimiter])
+  }
+
+  /// Sets the delimiters allowed in the document.
+  pub fn with_delimiters(mut self, delimiters: impl IntoIterator<Item = char>) -> Self {
+    self.delimiters = Some(delimiters.into_iter().collect());
+    self
+  }
+
+  /// Sets the fixed indentation unit and indentation character,
+  /// instead of taking them from the first indented line.
+  pub fn with_indent(mut self, unit: usize, ch: char) -> Self {
+    self.indent = Some((unit, ch));
+    self
+  }
+
+  /// Sets whether the last line of the document must end with a newline.
+  pub fn with_final_newline_required(mut self, required: bool) -> Self {
+    self.final_newline_required = required;
+    self
+  }
+
+  /// Sets the checking of indentation levels.
+  pub fn with_level_check(mut self, level_check: LevelCheck) -> Self {
+    self.level_check = level_check;
+    self
+  }
+
+  /// Sets the marker beginning comment lines.
+  /// Lines beginning with the marker, at any indentation, are comments.
+  /// When [escaping](Self::with_escaping) is enabled, content lines beginning with the marker are escaped with the delimiter preceding the marker.
+  ///
+  /// The delimiter is checked before the marker, lines beginning with the delimiter are never comments,
+  /// so a marker beginning with the delimiter of the document marks no comments.
+  /// Before the first node, the delimiter is known only when set with [with_delimiter](Self::with_delimiter)
+  /// or [with_delimiters](Self::with_delimiters), otherwise lines beginning with the marker are comments there.
+  pub fn with_comment_marker(mut self, marker: impl Into<String>) -> Self {
+    self.comment_marker = Some(marker.into());
+    self
+  }
+
+  /// Sets whether content lines beginning with the delimiter can be escaped.
+  /// When enabled, a line beginning with the doubled delimiter, or with the delimiter followed by the comment marker,
+  /// is a content line without the first delimiter, and node names must not begin with the delimiter nor the comment marker.
+  /// Disabled by default, such lines begin nodes with names beginning with the delimiter or the marker, as in earlier versions.
+  /// Text written from nodes whose content has lines beginning with the delimiter must be parsed with escaping enabled.
+  pub fn with_escaping(mut self, escaping: bool) -> Self {
+    self.escaping = escaping;
+    self
+  }
+
+  /// Sets whether any text may precede the first node.
+  /// The first node begins at the first line starting with an allowed delimiter,
+  /// so the preamble must be combined with [with_delimiter](Self::with_delimiter) or [with_delimiters](Self::with_delimiters),
+  /// otherwise parsing fails.
+  pub fn with_preamble_allowed(mut self, allowed: bool) -> Self {
+    self.preamble_allowed = allowed;
+    self
+  }
+
+  /// Sets the tab stop, accepting indentation that mixes spaces and tabs.
+  /// Each tab advances the indentation to the next multiple of the tab stop,
+  /// and the resulting width determines the level of the node.
+  /// Indentation mixing spaces and tabs is reported by nodes as spaces up to the same width.
+  pub fn with_tab_stop(mut self, tab_stop: usize) -> Self {
+    self.tab_stop = Some(tab_stop);
+    self
+  }
+
+  /// Sets whether the indentation unit is detected from all indented lines of the document,
+  /// instead of taking it from the first indented line.
+  /// The unit is the most common step between the indentation of consecutive nodes,
+  /// see [detect_indent](crate::detect_indent) for details. Ignored when the fixed indentation is set.
+  /// Detection requires the whole input, so it is not applied when reading from a stream.
+  pub fn with_indent_detection(mut self, detection: bool) -> Self {
+    self.indent_detection = detection;
+    self
+  }
+
+  /// Sets the maximum nesting depth of nodes, top level nodes have depth `1`.
+  ///
+  /// Limits are meant for parsing untrusted input. Exceeding any limit stops parsing,
+  /// also when continuing after errors, so the best-effort node tree contains only nodes parsed before.
+  pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+    self.max_depth = Some(max_depth);
+    self
+  }
+
+  /// Sets the maximum number of nodes in the document.
+  pub fn with_max_nodes(mut self, max_nodes: usize) -> Self {
+    self.max_nodes = Some(max_nodes);
+    self
+  }
+
+  /// Sets the maximum length of the node name in bytes, excluding the delimiter.
+  pub fn with_max_name_len(mut self, max_name_len: usize) -> Self {
+    self.max_name_len = Some(max_name_len);
+    self
+  }
+
+  /// Sets the maximum length of the node content in bytes, as written in the input,
+  /// including line endings and escaping delimiters.
+  pub fn with_max_content_len(mut self, max_content_len: usize) -> Self {
+    self.max_content_len = Some(max_content_len);
+    self
+  }
+
+  /// Sets the maximum length of the whole input in bytes.
+  /// When reading from a stream, no more than the limit is read from the reader.
+  pub fn with_max_input_len(mut self, max_input_len: usize) -> Self {
+    self.max_input_len = Some(max_input_len);
+    self
+  }
+
+  /// Returns the allowed delimiters, `None` when any delimiter is allowed.
+  pub fn delimiters(&self) -> Option<&[char]> {
+    self.delimiters.as_deref()
+  }
+
+  /// Returns the fixed indentation unit and indentation character, if set.
+  pub fn indent(&self) -> Option<(usize, char)> {
+    self.indent
+  }
+
+  /// Returns `true` when the last line of the document must end with a newline.
+  pub fn final_newline_required(&self) -> bool {
+    self.final_newline_required
+  }
+
+  /// Returns the checking of indentation levels.
+  pub fn level_check(&self) -> LevelCheck {
+    self.level_check
+  }
+
+  /// Returns the marker beginning comment lines, if set.
+  pub fn comment_marker(&self) -> Option<&str> {
+    self.comment_marker.as_deref()
+  }
+
+  /// Returns `true` when content lines beginning with the delimiter can be escaped.
+  pub fn escaping(&self) -> bool {
+    self.escaping
+  }
+
+  /// Returns `true` when any text may precede the first node.
+  pub fn preamble_allowed(&self) -> bool {
+    self.preamble_allowed
+  }
+
+  /// Returns the tab stop, if set.
+  pub fn tab_stop(&self) -> Option<usize> {
+    self.tab_stop
+  }
+
+  /// Returns `true` when the indentation unit is detected from all lines of the document.
+  pub fn indent_detection(&self) -> bool {
+    self.indent_detection
+  }
+
+  /// Returns the maximum nesting depth of nodes, if set.
+  pub fn max_depth(&self) -> Option<usize> {
+    self.max_depth
+  }
+
+  /// Returns the maximum number of nodes in the document, if set.
+  pub fn max_nodes(&self) -> Option<usize> {
+    self.max_nodes
+  }
+
+  /// Returns the maximum length of the node name in bytes, if set.
+  pub fn max_name_len(&self) -> Option<usize> {
+    self.max_name_len
+  }
+
+  /// Returns the maximum length of the node content in bytes, if set.
+  pub fn max_content_len(&self) -> Option<usize> {
+    self.max_content_len
+  }
+
+  /// Returns the maximum length of the whole input in bytes, if set.
+  pub fn max_input_len(&self) -> Option<usize> {
+    self.max_input_len
+  }
+
+  /// Returns the width of the indentation text in columns,
+  /// when the tab stop is set, tabs advance to the next multiple of the tab stop.
+  pub(crate) fn indentation_width(&self, indentation: &str) -> usize {
+    match self.tab_stop {
+      Some(tab_stop) => {
+        let tab_stop = tab_stop.max(1);
+        indentation
+          .chars()
+          .fold(0, |width, ch| if ch == TAB { (width / tab_stop + 1) * tab_stop } else { width + 1 })
+      }
+      None => indentation.chars().count(),
+    }
+  }
+
+  /// Returns the width in columns of the indentation made of the specified number of indentation characters.
+  pub(crate) fn indent_width(&self, indent: usize, indent_char: char) -> usize {
+    match self.tab_stop {
+      Some(tab_stop) if indent_char == TAB => indent * tab_stop.max(1),
+      _ => indent,
+    }
+  }
+
+  /// Returns `true` when the specified character is allowed as a delimiter.
+  pub(crate) fn is_allowed_delimiter(&self, ch: char) -> bool {
+    self.delimiters.as_ref().is_none_or(|delimiters| delimiters.contains(&ch))
+  }
+
+  /// Returns `true` when the specified text begins with the comment marker and not with a delimiter,
+  /// the delimiter of the document when already known, otherwise any delimiter set in options.
+  pub(crate) fn is_comment_start(&self, text: &str, delimiter: Option<char>) -> bool {
+    let is_delimiter = |ch: char| match delimiter {
+      Some(delimiter) => ch == delimiter,
+      None => self.delimiters.as_ref().is_some_and(|delimiters| delimiters.contains(&ch)),
+    };
+    self
+      .comment_marker
+      .as_ref()
+      .is_some_and(|marker| !marker.is_empty() && text.starts_with(marker.as_str()) && !text.starts_with(is_delimiter))
+  }
+}
@@ -1,128 +1,158 @@
 //! # Parser implementation
 
-use crate::defs::*;
+use crate::document::Document;
 use crate::errors::*;
-use crate::tokenizer::{tokenize, Token};
+use crate::events::{Event, EventParser};
+use crate::options::ParseOptions;
+use crate::tokenizer::{Token, Tokenizer};
 use crate::Node;
-use std::vec::IntoIter;
+use std::sync::Arc;
 
 /// Parses input text.
-pub fn parse(input: &str) -> Result<Node> {
-  Parser::new(tokenize(input)?).parse()
+pub fn parse(input: &str) -> Result<Document<'_>> {
+  parse_with_options(input, &ParseOptions::default())
 }
 
-/// Parser state.
-enum ParserState {
-  /// Expected the indentation token.
-  Indentation,
-  /// Expected the node name token.
-  NodeName,
-  /// Expected the node content token.
-  NodeContent,
+/// Parses input text with the specified options.
+pub fn parse_with_options<'a>(input: &'a str, options: &ParseOptions) -> Result<Document<'a>> {
+  Parser::with_events(EventParser::with_options(Tokenizer::with_options(input, options), options).with_detected_indent(input)).parse_document(Some(input))
+}
+
+/// Parses input text, continuing after errors.
+/// Returns the best-effort document and all errors found in the input.
+pub fn parse_recovering(input: &str) -> (Document<'_>, Vec<IdmlError>) {
+  parse_recovering_with_options(input, &ParseOptions::default())
+}
+
+/// Parses input text with the specified options, continuing after errors.
+/// Returns the best-effort document and all errors found in the input.
+pub fn parse_recovering_with_options<'a>(input: &'a str, options: &ParseOptions) -> (Document<'a>, Vec<IdmlError>) {
+  let (tokens, mut errors) = Tokenizer::with_options(input, options).tokenize_recovering();
+  let events = EventParser::with_options(tokens.into_iter().map(Ok), options).with_detected_indent(input);
+  let (document, parser_errors) = Parser::with_events(events).parse_document_recovering(Some(input));
+  errors.extend(parser_errors);
+  errors.sort_by_key(|err| err.offset());
+  // The same error may be reported by both the tokenizer and the event parser, different errors at the same position are kept.
+  errors.dedup_by(|err, other| err.position() == other.position() && err.kind() == other.kind());
+  (document, errors)
 }
 
 /// Parser.
-pub struct Parser {
-  state: ParserState,
-  tokens: IntoIter<Token>,
-  nodes: Vec<Node>,
-  stack: Vec<Node>,
-  first_indent: usize,
-  first_indent_char: char,
-  last_indent: usize,
-  last_indent_char: char,
-  last_name: String,
-  last_delimiter: char,
+///
+/// Builds the node tree from events reported by the [EventParser].
+pub struct Parser<'a> {
+  events: EventParser<'a>,
+  recovering: bool,
+  errors: Vec<IdmlError>,
 }
 
-impl Parser {
-  /// Creates a new instance of the parser.
-  pub fn new(tokens: Vec<Token>) -> Self {
+impl<'a> Parser<'a> {
+  /// Creates a new instance of the parser, processing the specified tokens.
+  pub fn new<I>(tokens: I) -> Self
+  where
+    I: IntoIterator<Item = Token<'a>>,
+    I::IntoIter: 'a,
+  {
+    Self::with_options(tokens, &ParseOptions::default())
+  }
+
+  /// Creates a new instance of the parser with the specified options, processing the specified tokens.
+  pub fn with_options<I>(tokens: I, options: &ParseOptions) -> Self
+  where
+    I: IntoIterator<Item = Token<'a>>,
+    I::IntoIter: 'a,
+  {
+    Self::with_events(EventParser::with_options(tokens.into_iter().map(Ok), options))
+  }
+
+  /// Creates a new instance of the parser, building the node tree from the specified events.
+  pub(crate) fn with_events(events: EventParser<'a>) -> Self {
     Self {
-      state: ParserState::Indentation,
-      tokens: tokens.into_iter(),
-      nodes: vec![],
-      stack: vec![],
-      first_indent: 0,
-      first_indent_char: NULL,
-      last_indent: 0,
-      last_indent_char: NULL,
-      last_name: "".to_string(),
-      last_delimiter: NULL,
+      events,
+      recovering: false,
+      errors: vec![],
     }
   }
 
   /// Parses the tokens.
-  pub fn parse(mut self) -> Result<Node> {
-    loop {
-      let Some(token) = self.tokens.next() else {
-        break;
-      };
-      match self.state {
-        ParserState::Indentation => {
-          if let Token::Indentation(indent, indent_char) = token {
-            if self.first_indent == 0 && indent > 0 {
-              self.first_indent = indent;
-              self.first_indent_char = indent_char;
-            }
-            self.last_indent = indent;
-            self.last_indent_char = indent_char;
-            self.state = ParserState::NodeName;
-          } else {
-            return Err(err_expected_indentation());
-          }
+  pub fn parse(mut self) -> Result<Node<'a>> {
+    self.build_tree()
+  }
+
+  /// Parses the tokens, continuing after errors.
+  /// Returns the best-effort node tree and all errors found.
+  pub fn parse_recovering(self) -> (Node<'a>, Vec<IdmlError>) {
+    let (document, errors) = self.parse_document_recovering(None);
+    (document.into_root(), errors)
+  }
+
+  /// Parses the tokens into a document with the specified input text.
+  pub(crate) fn parse_document(mut self, source: Option<&'a str>) -> Result<Document<'a>> {
+    let root = self.build_tree()?;
+    Ok(Document::new(root, source, self.events.indent()))
+  }
+
+  /// Parses the tokens into a document with the specified input text, continuing after errors.
+  pub(crate) fn parse_document_recovering(mut self, source: Option<&'a str>) -> (Document<'a>, Vec<IdmlError>) {
+    self.recovering = true;
+    self.events.set_recovering();
+    let root = self.build_tree().unwrap_or_else(|_| Node::root());
+    let mut errors = self.events.take_errors();
+    errors.append(&mut self.errors);
+    (Document::new(root, source, self.events.indent()), errors)
+  }
+
+  /// Builds the node tree from parsing events.
+  /// When recovering, nodes parsed before an error that stops parsing are kept in the tree.
+  fn build_tree(&mut self) -> Result<Node<'a>> {
+    let comment_marker = self.events.comment_marker().filter(|marker| !marker.is_empty()).map(Arc::from);
+    let mut root = Node::root();
+    root.set_comment_marker(comment_marker.clone());
+    let mut stack = vec![root];
+    let mut comments = vec![];
+    for event in self.events.by_ref() {
+      match event {
+        Ok(Event::StartNode {
+          level,
+          indentation,
+          delimiter,
+          name,
+          span,
+        }) => {
+          let mut node = Node::parsed(level, indentation, delimiter, name, span);
+          node.set_comments(std::mem::take(&mut comments));
+          node.set_comment_marker(comment_marker.clone());
+          stack.push(node);
         }
-        ParserState::NodeName => {
-          if let Token::NodeName(name, delimiter) = token {
-            self.last_name = name;
-            self.last_delimiter = delimiter;
-            self.state = ParserState::NodeContent;
-          } else {
-            return Err(err_expected_node_name());
+        Ok(Event::Content(content, span)) => {
+          // Comments reported after the node was started interrupt its content.
+          let interrupted = stack.len() > 1 && !comments.is_empty();
+          if let Some(node) = stack.last_mut() {
+            if interrupted {
+              node.continue_parsed_content(std::mem::take(&mut comments), content, span);
+            } else {
+              node.set_parsed_content(content, span);
+            }
           }
         }
-        ParserState::NodeContent => {
-          if let Token::NodeContent(content) = token {
-            self.create_node(self.last_indent, self.last_indent_char, self.last_delimiter, self.last_name.clone(), content)?;
-            self.last_indent = 0;
-            self.last_name = "".to_string();
-            self.last_delimiter = NULL;
-            self.state = ParserState::Indentation;
-          } else {
-            return Err(err_expected_node_content());
+        Ok(Event::Comment(comment, _)) => comments.push(comment),
+        Ok(Event::Preamble(preamble, span)) => stack[0].set_parsed_content(preamble, span),
+        Ok(Event::EndNode) => {
+          if stack.len() > 1 {
+            let node = stack.pop().unwrap();
+            stack.last_mut().unwrap().add_child(node);
           }
         }
+        Err(err) if self.recovering => self.errors.push(err),
+        Err(err) => return Err(err),
       }
     }
-    for mut node in self.nodes.drain(..).rev() {
-      if self.stack.is_empty() {
-        self.stack.push(node);
-      } else {
-        while !self.stack.is_empty() && node.level() < self.stack.last().unwrap().level() {
-          node.add_child(self.stack.pop().unwrap());
-        }
-        self.stack.push(node);
-      }
-    }
-    let mut root = Node::root();
-    while let Some(node) = self.stack.pop() {
-      root.add_child(node);
+    while stack.len() > 1 {
+      let node = stack.pop().unwrap();
+      stack.last_mut().unwrap().add_child(node);
     }
+    let mut root = stack.pop().unwrap();
+    root.set_comments(comments);
     Ok(root)
   }
-
-  /// Creates a new node and adds it to the parsed node list.
-  fn create_node(&mut self, indent: usize, indent_char: char, delimiter: char, name: String, content: String) -> Result<()> {
-    let multiplier = self.first_indent;
-    if multiplier > 0 && indent % multiplier != 0 {
-      return Err(err_malformed_indentation(indent, multiplier));
-    }
-    if indent > 0 && indent_char != self.first_indent_char {
-      return Err(err_inconsistent_indentation());
-    }
-    let level = if multiplier > 0 { (indent / multiplier) + 1 } else { 1 };
-    let node = Node::new(level, delimiter, name, content);
-    self.nodes.push(node);
-    Ok(())
-  }
 }
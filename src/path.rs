@@ -0,0 +1,517 @@
+//! # Path query implementation
+
+use crate::errors::*;
+use crate::node::Node;
+use crate::position::Position;
+use std::collections::HashSet;
+use std::fmt;
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// Compiled path query selecting nodes in the tree.
+///
+/// The path consists of steps separated with `/`, each step selects nodes relative to the nodes selected by the preceding step:
+/// - `name` selects child nodes having the specified name,
+/// - `name|other` selects child nodes having any of the specified names,
+/// - `"name"` selects child nodes having the quoted name, `""` selects anonymous nodes with empty names,
+/// - `*` selects all child nodes,
+/// - `**` selects the node itself and all its descendant nodes, at any depth,
+/// - `[n]` following any of the above keeps only the `n`-th selected node, counting from zero,
+///   without preceding name it selects the `n`-th child node,
+/// - `[predicate]` following any of the above keeps only the selected nodes matching the predicate.
+///
+/// Filters are applied in order, so `*[born="2001"][0]` selects the first node matching the predicate.
+/// Names containing whitespace or any of the characters `/[]|"*=!~()` must be quoted.
+///
+/// Predicates compare the text of nodes selected by a relative path with a quoted value,
+/// the predicate is matched when the text of any selected node matches:
+/// - `path` matches when the path selects any node,
+/// - `path="value"` the text is equal to the value, `path!="value"` the text differs from the value,
+/// - `path~="value"` the text contains the value,
+/// - `path starts-with "value"` and `path ends-with "value"` the text starts or ends with the value,
+/// - `text` in place of the path refers to the text of the filtered node itself, child nodes named `text` are selected with `"text"`,
+/// - predicates are combined with `and`, `or`, `not(...)` and parentheses, `and` takes precedence over `or`.
+///
+/// Filters, parentheses and negations may be nested at most 64 levels deep.
+///
+/// Nodes are returned in document order, each node at most once.
+///
+/// ```
+/// use idml::{parse, Path};
+///
+/// let root = parse(".tutorial\n  .idML\n    .type Brilliant!\n  .yaml\n    .type Awesome!\n").unwrap();
+/// let path = Path::new("tutorial/*/type").unwrap();
+/// let types = path.select(&root).map(|node| node.text()).collect::<Vec<&str>>();
+/// assert_eq!(vec!["Brilliant!", "Awesome!"], types);
+/// let path = Path::new(r#"tutorial/*[type~="Awe" or text="idML"]"#).unwrap();
+/// assert_eq!("yaml", path.select(&root).next().unwrap().name());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Path {
+  /// Original path expression.
+  expression: String,
+  /// Compiled steps of the path.
+  steps: Vec<Step>,
+}
+
+/// Single step of the path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Step {
+  /// Test selecting the nodes.
+  test: Test,
+  /// Filters applied to the selected nodes.
+  filters: Vec<Filter>,
+}
+
+/// Test selecting the nodes in a single step.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Test {
+  /// Child nodes having any of the names.
+  Names(Vec<String>),
+  /// All child nodes.
+  Children,
+  /// The node itself and all descendant nodes.
+  Descendants,
+}
+
+/// Filter applied to the nodes selected in a single step.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Filter {
+  /// Node at the specified position, counting from zero.
+  Index(usize),
+  /// Nodes matching the predicate.
+  Predicate(Predicate),
+}
+
+/// Predicate matched against a single node.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Predicate {
+  /// All predicates are matched.
+  And(Vec<Predicate>),
+  /// Any of the predicates is matched.
+  Or(Vec<Predicate>),
+  /// The predicate is not matched.
+  Not(Box<Predicate>),
+  /// The operand selects any node.
+  Exists(Operand),
+  /// The text of any node selected by the operand is matched by the operator with the value.
+  Compare(Operand, Operator, String),
+}
+
+/// Operand of the predicate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Operand {
+  /// The node itself.
+  Text,
+  /// Nodes selected by the relative path.
+  Path(Vec<Step>),
+}
+
+/// Operator comparing the text with the value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Operator {
+  /// `=`
+  Equals,
+  /// `!=`
+  NotEquals,
+  /// `~=`
+  Contains,
+  /// `starts-with`
+  StartsWith,
+  /// `ends-with`
+  EndsWith,
+}
+
+impl FromStr for Path {
+  type Err = IdmlError;
+
+  /// Compiles the path from the specified expression.
+  fn from_str(expression: &str) -> Result<Self> {
+    Path::new(expression)
+  }
+}
+
+impl Display for Path {
+  /// Implementation of [Display] trait for [Path], displays the original expression.
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}", self.expression)
+  }
+}
+
+impl Path {
+  /// Compiles the path from the specified expression.
+  /// Errors are reported at the position in the expression, in the first row.
+  pub fn new(expression: &str) -> Result<Self> {
+    let steps = PathParser::new(expression).parse()?;
+    Ok(Self {
+      expression: expression.to_string(),
+      steps,
+    })
+  }
+
+  /// Returns the original path expression.
+  pub fn as_str(&self) -> &str {
+    &self.expression
+  }
+
+  /// Returns an iterator over nodes selected by this path, relative to the specified node.
+  pub fn select<'b, 'a>(&self, node: &'b Node<'a>) -> impl Iterator<Item = &'b Node<'a>> {
+    select(&self.steps, node).into_iter()
+  }
+}
+
+/// Returns nodes selected by the steps, relative to the specified node.
+fn select<'b, 'a>(steps: &[Step], node: &'b Node<'a>) -> Vec<&'b Node<'a>> {
+  let mut current = vec![node];
+  // Context nodes are nested after selecting descendants.
+  let mut nested = false;
+  for step in steps {
+    let mut selected = vec![];
+    for context in current {
+      step.select(context, &mut selected);
+    }
+    current = if nested { in_document_order(node, selected) } else { selected };
+    nested |= step.test == Test::Descendants;
+  }
+  current
+}
+
+/// Returns the selected nodes in document order, each node at most once.
+/// Nodes selected relative to nested context nodes are neither ordered nor unique.
+fn in_document_order<'b, 'a>(node: &'b Node<'a>, selected: Vec<&'b Node<'a>>) -> Vec<&'b Node<'a>> {
+  let selected = selected.into_iter().map(|node| node as *const Node).collect::<HashSet<_>>();
+  std::iter::once(node)
+    .chain(node.descendants())
+    .filter(|node| selected.contains(&(*node as *const Node)))
+    .take(selected.len())
+    .collect()
+}
+
+impl Step {
+  /// Appends nodes selected by this step, relative to the specified context node.
+  fn select<'b, 'a>(&self, context: &'b Node<'a>, selected: &mut Vec<&'b Node<'a>>) {
+    let mut nodes = match &self.test {
+      Test::Names(names) => context.children().filter(|node| names.iter().any(|name| name == node.name())).collect(),
+      Test::Children => context.children().collect(),
+      Test::Descendants => {
+        let mut nodes = vec![];
+        let mut stack = vec![context];
+        while let Some(node) = stack.pop() {
+          nodes.push(node);
+          stack.extend(node.children().rev());
+        }
+        nodes
+      }
+    };
+    for filter in &self.filters {
+      nodes = match filter {
+        Filter::Index(index) => nodes.get(*index).into_iter().copied().collect(),
+        Filter::Predicate(predicate) => nodes.into_iter().filter(|node| predicate.matches(node)).collect(),
+      };
+    }
+    selected.extend(nodes);
+  }
+}
+
+impl Predicate {
+  /// Returns `true` when the specified node matches this predicate.
+  fn matches(&self, node: &Node) -> bool {
+    match self {
+      Predicate::And(predicates) => predicates.iter().all(|predicate| predicate.matches(node)),
+      Predicate::Or(predicates) => predicates.iter().any(|predicate| predicate.matches(node)),
+      Predicate::Not(predicate) => !predicate.matches(node),
+      Predicate::Exists(operand) => operand.select(node).next().is_some(),
+      Predicate::Compare(operand, operator, value) => operand.select(node).any(|node| operator.matches(node.text(), value)),
+    }
+  }
+}
+
+impl Operand {
+  /// Returns an iterator over nodes selected by this operand, relative to the specified node.
+  fn select<'b, 'a>(&self, node: &'b Node<'a>) -> impl Iterator<Item = &'b Node<'a>> {
+    match self {
+      Operand::Text => vec![node],
+      Operand::Path(steps) => select(steps, node),
+    }
+    .into_iter()
+  }
+}
+
+impl Operator {
+  /// Returns `true` when the text is matched by this operator with the value.
+  fn matches(&self, text: &str, value: &str) -> bool {
+    match self {
+      Operator::Equals => text == value,
+      Operator::NotEquals => text != value,
+      Operator::Contains => text.contains(value),
+      Operator::StartsWith => text.starts_with(value),
+      Operator::EndsWith => text.ends_with(value),
+    }
+  }
+}
+
+/// Maximum nesting of filters, parentheses and negations in the path expression.
+const MAX_NESTING: usize = 64;
+
+/// Parser of path expressions.
+struct PathParser<'a> {
+  /// Path expression.
+  expression: &'a str,
+  /// Byte offset of the currently processed character.
+  offset: usize,
+  /// Nesting of the currently parsed filter, parentheses or negation.
+  nesting: usize,
+}
+
+impl<'a> PathParser<'a> {
+  /// Creates a new parser of the specified path expression.
+  fn new(expression: &'a str) -> Self {
+    Self {
+      expression,
+      offset: 0,
+      nesting: 0,
+    }
+  }
+
+  /// Parses all steps of the path, a single leading `/` is allowed.
+  fn parse(mut self) -> Result<Vec<Step>> {
+    self.eat('/');
+    let steps = self.steps()?;
+    match self.peek() {
+      None => Ok(steps),
+      Some(ch) => Err(self.error(format!("unexpected character '{ch}'"))),
+    }
+  }
+
+  /// Parses steps separated with `/`.
+  fn steps(&mut self) -> Result<Vec<Step>> {
+    let mut steps = vec![self.step()?];
+    while self.eat('/') {
+      steps.push(self.step()?);
+    }
+    Ok(steps)
+  }
+
+  /// Parses a single step.
+  fn step(&mut self) -> Result<Step> {
+    let test = if self.rest().starts_with("**") {
+      self.offset += 2;
+      Test::Descendants
+    } else if self.eat('*') || matches!(self.peek(), Some('[')) {
+      Test::Children
+    } else {
+      let mut names = vec![self.name()?];
+      while self.eat('|') {
+        names.push(self.name()?);
+      }
+      Test::Names(names)
+    };
+    let mut filters = vec![];
+    while self.eat('[') {
+      filters.push(self.nested(Self::filter)?);
+      if !self.eat(']') {
+        return Err(self.error("expected ']'"));
+      }
+    }
+    Ok(Step { test, filters })
+  }
+
+  /// Parses a bare or quoted node name.
+  fn name(&mut self) -> Result<String> {
+    if self.eat('"') {
+      return self.quoted();
+    }
+    let start = self.offset;
+    while let Some(ch) = self.peek().filter(|ch| is_name_char(*ch)) {
+      self.offset += ch.len_utf8();
+    }
+    if start == self.offset {
+      return Err(self.error("expected node name"));
+    }
+    Ok(self.expression[start..self.offset].to_string())
+  }
+
+  /// Parses the rest of the quoted text, the opening quote is already consumed.
+  /// Quotes and backslashes inside are escaped with a backslash.
+  fn quoted(&mut self) -> Result<String> {
+    let mut text = String::new();
+    loop {
+      match self.next() {
+        Some('"') => return Ok(text),
+        Some('\\') => match self.next() {
+          Some(ch @ ('"' | '\\')) => text.push(ch),
+          _ => return Err(self.error("invalid escape sequence")),
+        },
+        Some(ch) => text.push(ch),
+        None => return Err(self.error("expected closing quote")),
+      }
+    }
+  }
+
+  /// Parses a filter, the opening bracket is already consumed.
+  fn filter(&mut self) -> Result<Filter> {
+    self.skip_whitespace();
+    if !self.peek().is_some_and(|ch| ch.is_ascii_digit()) {
+      let predicate = self.or()?;
+      self.skip_whitespace();
+      return Ok(Filter::Predicate(predicate));
+    }
+    let start = self.offset;
+    while let Some(ch) = self.peek().filter(char::is_ascii_digit) {
+      self.offset += ch.len_utf8();
+    }
+    let digits = &self.expression[start..self.offset];
+    let index = digits.parse().map_err(|_| self.error("index out of range"))?;
+    self.skip_whitespace();
+    Ok(Filter::Index(index))
+  }
+
+  /// Parses predicates combined with `or`.
+  fn or(&mut self) -> Result<Predicate> {
+    let mut predicates = vec![self.and()?];
+    while self.keyword("or") {
+      predicates.push(self.and()?);
+    }
+    Ok(if predicates.len() == 1 { predicates.remove(0) } else { Predicate::Or(predicates) })
+  }
+
+  /// Parses predicates combined with `and`.
+  fn and(&mut self) -> Result<Predicate> {
+    let mut predicates = vec![self.unary()?];
+    while self.keyword("and") {
+      predicates.push(self.unary()?);
+    }
+    Ok(if predicates.len() == 1 { predicates.remove(0) } else { Predicate::And(predicates) })
+  }
+
+  /// Parses negated predicate, predicate in parentheses or comparison.
+  fn unary(&mut self) -> Result<Predicate> {
+    self.skip_whitespace();
+    if self.keyword("not") {
+      self.skip_whitespace();
+      if !self.eat('(') {
+        return Err(self.error("expected '('"));
+      }
+      return Ok(Predicate::Not(Box::new(self.nested(Self::parenthesized)?)));
+    }
+    if self.eat('(') {
+      return self.nested(Self::parenthesized);
+    }
+    self.comparison()
+  }
+
+  /// Parses the rest of the predicate in parentheses, the opening parenthesis is already consumed.
+  fn parenthesized(&mut self) -> Result<Predicate> {
+    let predicate = self.or()?;
+    self.skip_whitespace();
+    if !self.eat(')') {
+      return Err(self.error("expected ')'"));
+    }
+    Ok(predicate)
+  }
+
+  /// Parses the nested part of the expression with the specified function,
+  /// reports an error when the nesting limit is exceeded.
+  fn nested<T>(&mut self, parse: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+    if self.nesting == MAX_NESTING {
+      return Err(self.error("nesting too deep"));
+    }
+    self.nesting += 1;
+    let result = parse(self);
+    self.nesting -= 1;
+    result
+  }
+
+  /// Parses the operand, optionally followed by the operator and the quoted value.
+  fn comparison(&mut self) -> Result<Predicate> {
+    let operand = if self.keyword("text") { Operand::Text } else { Operand::Path(self.steps()?) };
+    self.skip_whitespace();
+    let operator = if self.eat('=') {
+      Operator::Equals
+    } else if self.symbol("!=") {
+      Operator::NotEquals
+    } else if self.symbol("~=") {
+      Operator::Contains
+    } else if self.keyword("starts-with") {
+      Operator::StartsWith
+    } else if self.keyword("ends-with") {
+      Operator::EndsWith
+    } else {
+      return Ok(Predicate::Exists(operand));
+    };
+    self.skip_whitespace();
+    if !self.eat('"') {
+      return Err(self.error("expected quoted value"));
+    }
+    Ok(Predicate::Compare(operand, operator, self.quoted()?))
+  }
+
+  /// Consumes the specified symbol, returns `true` when the unprocessed part of the expression starts with it.
+  fn symbol(&mut self, symbol: &str) -> bool {
+    let matched = self.rest().starts_with(symbol);
+    if matched {
+      self.offset += symbol.len();
+    }
+    matched
+  }
+
+  /// Consumes the specified keyword preceded by optional whitespace,
+  /// returns `true` when the keyword is not followed by any other name character.
+  fn keyword(&mut self, keyword: &str) -> bool {
+    let start = self.offset;
+    self.skip_whitespace();
+    if let Some(rest) = self.rest().strip_prefix(keyword) {
+      if !rest.starts_with(|ch: char| is_name_char(ch) || ch == '/' || ch == '[') {
+        self.offset += keyword.len();
+        return true;
+      }
+    }
+    self.offset = start;
+    false
+  }
+
+  /// Skips whitespace characters.
+  fn skip_whitespace(&mut self) {
+    while let Some(ch) = self.peek().filter(|ch| ch.is_whitespace()) {
+      self.offset += ch.len_utf8();
+    }
+  }
+
+  /// Consumes the specified character, returns `true` when it was the current character.
+  fn eat(&mut self, expected: char) -> bool {
+    if self.peek() == Some(expected) {
+      self.offset += expected.len_utf8();
+      true
+    } else {
+      false
+    }
+  }
+
+  /// Consumes and returns the current character.
+  fn next(&mut self) -> Option<char> {
+    let ch = self.peek()?;
+    self.offset += ch.len_utf8();
+    Some(ch)
+  }
+
+  /// Returns the current character.
+  fn peek(&self) -> Option<char> {
+    self.rest().chars().next()
+  }
+
+  /// Returns the unprocessed part of the expression.
+  fn rest(&self) -> &'a str {
+    &self.expression[self.offset..]
+  }
+
+  /// Returns an error with the specified reason, at the current position in the expression.
+  fn error(&self, reason: impl Into<String>) -> IdmlError {
+    let column = self.expression[..self.offset].chars().count() + 1;
+    err_invalid_path(reason, Position::new(1, column, self.offset))
+  }
+}
+
+/// Returns `true` when the specified character may be a part of the bare name.
+fn is_name_char(ch: char) -> bool {
+  !matches!(ch, '/' | '[' | ']' | '|' | '"' | '*' | '=' | '!' | '~' | '(' | ')') && !ch.is_whitespace()
+}
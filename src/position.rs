@@ -0,0 +1,119 @@
+//! # Positions in processed input
+
+use std::fmt;
+use std::fmt::Display;
+
+/// Position of a character in processed input.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Position {
+  /// Row number, starting from `1`.
+  row: usize,
+  /// Column number (counted in characters), starting from `1`.
+  column: usize,
+  /// Byte offset from the beginning of the input, starting from `0`.
+  offset: usize,
+}
+
+impl Display for Position {
+  /// Implementation of [Display] trait for [Position].
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}:{}", self.row, self.column)
+  }
+}
+
+impl Position {
+  /// Creates a new position.
+  pub fn new(row: usize, column: usize, offset: usize) -> Self {
+    Self { row, column, offset }
+  }
+
+  /// Returns the row number, starting from `1`.
+  pub fn row(&self) -> usize {
+    self.row
+  }
+
+  /// Returns the column number, starting from `1`.
+  pub fn column(&self) -> usize {
+    self.column
+  }
+
+  /// Returns the byte offset from the beginning of the input.
+  pub fn offset(&self) -> usize {
+    self.offset
+  }
+
+  /// Returns the position just after the specified text, when the text begins at this position.
+  pub(crate) fn advanced(&self, text: &str) -> Position {
+    let mut position = *self;
+    let mut chars = text.chars().peekable();
+    while let Some(ch) = chars.next() {
+      position.offset += ch.len_utf8();
+      match ch {
+        '\r' if chars.peek() == Some(&'\n') => {}
+        '\n' | '\r' => {
+          position.row += 1;
+          position.column = 1;
+        }
+        _ => position.column += 1,
+      }
+    }
+    position
+  }
+
+  /// Returns this position, relative to the beginning of a text starting at the specified origin,
+  /// converted to the position relative to the beginning of the whole input.
+  pub(crate) fn shifted(&self, origin: Position) -> Position {
+    if self.row == 1 {
+      Position::new(origin.row, origin.column + self.column - 1, origin.offset + self.offset)
+    } else {
+      Position::new(origin.row + self.row - 1, self.column, origin.offset + self.offset)
+    }
+  }
+}
+
+/// Span of processed input, between two positions.
+///
+/// The start position points to the first character within the span,
+/// the end position points to the first character after the span.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Span {
+  /// Position of the first character within the span.
+  start: Position,
+  /// Position of the first character after the span.
+  end: Position,
+}
+
+impl Display for Span {
+  /// Implementation of [Display] trait for [Span].
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}-{}", self.start, self.end)
+  }
+}
+
+impl Span {
+  /// Creates a new span.
+  pub fn new(start: Position, end: Position) -> Self {
+    Self { start, end }
+  }
+
+  /// Returns the position of the first character within the span.
+  pub fn start(&self) -> Position {
+    self.start
+  }
+
+  /// Returns the position of the first character after the span.
+  pub fn end(&self) -> Position {
+    self.end
+  }
+
+  /// Returns this span, relative to the beginning of a text starting at the specified origin,
+  /// converted to the span relative to the beginning of the whole input.
+  pub(crate) fn shifted(&self, origin: Position) -> Span {
+    Span::new(self.start.shifted(origin), self.end.shifted(origin))
+  }
+
+  /// Returns `true` when the span contains no characters.
+  pub fn is_empty(&self) -> bool {
+    self.start.offset() >= self.end.offset()
+  }
+}
@@ -0,0 +1,330 @@
+//! # Streaming tokenizer implementation
+
+use crate::defs::*;
+use crate::errors::*;
+use crate::events::EventParser;
+use crate::options::ParseOptions;
+use crate::position::Position;
+use crate::tokenizer::{is_allowed_char, Token, Tokenizer};
+use crate::{Document, Parser};
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, Read};
+use std::marker::PhantomData;
+
+/// Parses text read from the specified reader.
+/// The input text is not retained in the returned document.
+pub fn parse_reader<R: Read>(reader: R) -> Result<Document<'static>> {
+  parse_reader_with_options(reader, &ParseOptions::default())
+}
+
+/// Parses text read from the specified reader, with the specified options.
+pub fn parse_reader_with_options<R: Read>(reader: R, options: &ParseOptions) -> Result<Document<'static>> {
+  let tokens = ReaderTokens {
+    tokenizer: StreamTokenizer::with_options(BufReader::new(reader), options),
+    reader: PhantomData,
+  };
+  // Parsed document borrows nothing from the reader, it only has to be typed as owned.
+  Ok(Parser::with_events(EventParser::with_options(tokens, options)).parse_document(None)?.into_owned())
+}
+
+/// Tokens read by the streaming tokenizer, typed with the lifetime of the reader,
+/// so the event parser does not require the reader to be `'static`.
+struct ReaderTokens<'r, R: BufRead> {
+  /// Tokenizer reading the input.
+  tokenizer: StreamTokenizer<R>,
+  /// Lifetime of the reader.
+  reader: PhantomData<&'r ()>,
+}
+
+impl<'r, R: BufRead> Iterator for ReaderTokens<'r, R> {
+  type Item = Result<Token<'r>>;
+
+  /// Returns the next owned token.
+  fn next(&mut self) -> Option<Self::Item> {
+    self.tokenizer.next()
+  }
+}
+
+/// Number of bytes read from the input at once, while the line is not read completely.
+const READ_LEN: usize = 8 * 1024;
+
+/// Tokenizer reading the input incrementally from a buffered reader.
+///
+/// The input is read line by line and tokenized node by node,
+/// so only the currently processed node is held in memory.
+/// The lengths of node names and contents are checked against the limits set in options while reading,
+/// so a node exceeding the limits is not read any further.
+pub struct StreamTokenizer<R: BufRead> {
+  /// Reader of the input.
+  reader: R,
+  /// Delimiter used in processed document, recognized in the first line.
+  delimiter: Option<char>,
+  /// Lines already read from the input, but not yet tokenized.
+  lines: VecDeque<String>,
+  /// The position of the first line not yet tokenized.
+  position: Position,
+  /// The position just after the last line read from the input.
+  read_position: Position,
+  /// The state of checking the lines taken for tokenizing against the length limits.
+  checked: LengthCheck,
+  /// Tokens already recognized, but not yet returned.
+  tokens: VecDeque<Token<'static>>,
+  /// Buffer for bytes read from the input, holding the line not read completely yet.
+  buffer: Vec<u8>,
+  /// Tokenizing options.
+  options: ParseOptions,
+  /// Flag indicating if the whole input was read or an error was reported.
+  done: bool,
+}
+
+impl<R: BufRead> Iterator for StreamTokenizer<R> {
+  type Item = Result<Token<'static>>;
+
+  /// Returns the next token read from the input.
+  fn next(&mut self) -> Option<Self::Item> {
+    while self.tokens.is_empty() && !self.done {
+      if let Err(err) = self.tokenize_node() {
+        self.done = true;
+        return Some(Err(err));
+      }
+    }
+    self.tokens.pop_front().map(Ok)
+  }
+}
+
+impl<R: BufRead> StreamTokenizer<R> {
+  /// Creates a new instance of the streaming tokenizer.
+  pub fn new(reader: R) -> Self {
+    Self::with_options(reader, &ParseOptions::default())
+  }
+
+  /// Creates a new instance of the streaming tokenizer with the specified options.
+  pub fn with_options(reader: R, options: &ParseOptions) -> Self {
+    Self {
+      reader,
+      delimiter: None,
+      lines: VecDeque::new(),
+      position: Position::new(1, 1, 0),
+      read_position: Position::new(1, 1, 0),
+      checked: LengthCheck::default(),
+      tokens: VecDeque::new(),
+      buffer: vec![],
+      options: options.clone(),
+      done: false,
+    }
+  }
+
+  /// Reads the lines of the next node and tokenizes them.
+  fn tokenize_node(&mut self) -> Result<()> {
+    let origin = self.position;
+    let mut chunk = String::new();
+    if self.fill_lines()? {
+      let mut line = self.take_line()?;
+      if origin.offset() == 0 {
+        // Lines preceding the first node are tokenized together with the first node.
+        if let Some(rest) = line.strip_prefix(BOM) {
+          chunk.push(BOM);
+          line = rest.to_string();
+        }
+        while self.precedes_first_node(&line) && self.fill_lines()? {
+          chunk.push_str(&line);
+          line = self.take_line()?;
+        }
+        self.delimiter = line.chars().next().filter(|ch| *ch > WS);
+      }
+      chunk.push_str(&line);
+      while self.fill_lines()? && !self.is_node_line(&self.lines[0]) {
+        let line = self.take_line()?;
+        chunk.push_str(&line);
+      }
+    } else {
+      self.done = true;
+      if origin.offset() > 0 {
+        return Ok(());
+      }
+    }
+    self.position = origin.advanced(&chunk);
+    let tokenizer = match self.delimiter {
+      Some(delimiter) if origin.offset() > 0 => Tokenizer::continued(&chunk, delimiter, &self.options),
+      _ => Tokenizer::with_options(&chunk, &self.options),
+    };
+    let tokens = tokenizer.tokenize().map_err(|err| err.shifted(origin))?;
+    self.tokens.extend(tokens.into_iter().map(|token| token.into_owned().shifted(origin)));
+    Ok(())
+  }
+
+  /// Takes the next line read from the input, checking it against the length limits.
+  fn take_line(&mut self) -> Result<String> {
+    let line = self.lines.pop_front().unwrap_or_default();
+    self.checked = self.check_line(&line, self.checked, false)?;
+    Ok(line)
+  }
+
+  /// Ensures there is at least one line read from the input and not yet tokenized.
+  /// Returns `false` when the whole input was already read.
+  fn fill_lines(&mut self) -> Result<bool> {
+    while self.lines.is_empty() {
+      // The line not read completely is read in growing parts, so checking it takes linear time.
+      let mut len = self.buffer.len().max(READ_LEN) as u64;
+      if let Some(limit) = self.options.max_input_len() {
+        // No more than one byte above the input length limit is read.
+        len = len.min(limit.saturating_sub(self.read_position.offset() + self.buffer.len()) as u64 + 1);
+      }
+      let mut reader = (&mut self.reader).take(len);
+      let end = reader.read_until(b'\n', &mut self.buffer).map_err(|err| err_io(err, self.read_position))? == 0;
+      if let Some(limit) = self.options.max_input_len().filter(|limit| self.read_position.offset() + self.buffer.len() > *limit) {
+        let within = &self.buffer[..limit - self.read_position.offset()];
+        let valid = match std::str::from_utf8(within) {
+          Ok(text) => text,
+          Err(err) => std::str::from_utf8(&within[..err.valid_up_to()]).unwrap_or_default(),
+        };
+        return Err(err_input_too_long(limit, self.read_position.advanced(valid)));
+      }
+      let text = match std::str::from_utf8(&self.buffer) {
+        Ok(text) => text,
+        // The last character may be read only partially.
+        Err(err) if err.error_len().is_none() && !end => std::str::from_utf8(&self.buffer[..err.valid_up_to()]).unwrap_or_default(),
+        Err(err) => {
+          let valid = std::str::from_utf8(&self.buffer[..err.valid_up_to()]).unwrap_or_default();
+          return Err(err_invalid_utf8(self.read_position.advanced(valid)));
+        }
+      };
+      // A single read may contain several lines ended with a carriage return,
+      // the carriage return at the end of the read may be followed by a newline.
+      let mut start = 0;
+      let mut chars = text.char_indices().peekable();
+      while let Some((index, ch)) = chars.next() {
+        let ended = match ch {
+          '\n' => true,
+          '\r' => chars.peek().map_or(end, |(_, next)| *next != '\n'),
+          _ => false,
+        };
+        if ended {
+          self.lines.push_back(text[start..=index].to_string());
+          start = index + 1;
+        }
+      }
+      if end && start < text.len() {
+        self.lines.push_back(text[start..].to_string());
+        start = text.len();
+      }
+      if self.lines.is_empty() && !end {
+        self.check_line(&text[start..], self.checked, true)?;
+      }
+      self.read_position = self.read_position.advanced(&text[..start]);
+      self.buffer.drain(..start);
+      if end {
+        break;
+      }
+    }
+    Ok(!self.lines.is_empty())
+  }
+
+  /// Checks the line following the already checked lines against the name and content length limits,
+  /// returns the state of the check after the line. The partially read line is checked as far as it was read.
+  fn check_line(&self, line: &str, checked: LengthCheck, partial: bool) -> Result<LengthCheck> {
+    let (line, position) = match line.strip_prefix(BOM) {
+      Some(rest) if checked.position.offset() == 0 => (rest, checked.position.advanced(&line[..BOM.len_utf8()])),
+      _ => (line, checked.position),
+    };
+    let next = LengthCheck {
+      position: position.advanced(line),
+      ..checked
+    };
+    if self.options.max_name_len().is_none() && self.options.max_content_len().is_none() {
+      return Ok(next);
+    }
+    let indented = line.trim_start_matches([WS, TAB]);
+    if self.is_comment_line(line) || (checked.len.is_none() && self.precedes_first_node(line)) {
+      return Ok(LengthCheck {
+        interrupted: checked.len.is_some(),
+        ..next
+      });
+    }
+    if checked.len.is_none() || self.is_node_line(line) {
+      let name_start = line.len() - indented.len() + indented.chars().next().map_or(0, char::len_utf8);
+      let name_len = line[name_start..].find(|ch| !is_allowed_char(ch)).unwrap_or(line.len() - name_start);
+      if let Some(limit) = self.options.max_name_len().filter(|limit| name_len > *limit) {
+        let (index, _) = line[name_start..].char_indices().find(|(index, ch)| index + ch.len_utf8() > limit).unwrap_or_default();
+        return Err(err_name_too_long(limit, position.advanced(&line[..name_start + index])));
+      }
+      let content_start = name_start + name_len;
+      if line[content_start..].starts_with(|ch| ch != WS && ch != TAB && ch != '\r' && ch != '\n') {
+        // Unexpected character in the name is reported by the tokenizer.
+        return Ok(LengthCheck { len: Some(0), ..next });
+      }
+      return self.check_content(line, content_start, 0, position, next);
+    }
+    if indented.is_empty() && partial {
+      // The indentation may precede a node or a comment, it is not known to be the content yet.
+      return Ok(checked);
+    }
+    if checked.interrupted && indented.trim_start_matches(['\r', '\n']).is_empty() {
+      // Blank lines following the comment belong to the comment.
+      return Ok(next);
+    }
+    let before = if checked.interrupted { 0 } else { checked.len.unwrap_or_default() };
+    self.check_content(line, 0, before, position, next)
+  }
+
+  /// Checks the content beginning at the specified index in the line, following content of the specified length,
+  /// against the content length limit. The line begins at the specified position.
+  fn check_content(&self, line: &str, start: usize, before: usize, position: Position, next: LengthCheck) -> Result<LengthCheck> {
+    let len = before + line.len() - start;
+    if let Some(limit) = self.options.max_content_len().filter(|limit| len > *limit) {
+      let within = (start..=start + limit - before).rev().find(|index| line.is_char_boundary(*index)).unwrap_or(start);
+      return Err(err_content_too_long(limit, position.advanced(&line[..within])));
+    }
+    Ok(LengthCheck {
+      len: Some(len),
+      interrupted: false,
+      ..next
+    })
+  }
+
+  /// Returns `true` when the specified line may precede the first node:
+  /// a blank line, a comment line or a preamble line.
+  fn precedes_first_node(&self, line: &str) -> bool {
+    if line.trim().is_empty() || self.is_comment_line(line) {
+      return true;
+    }
+    self.options.preamble_allowed() && !line.chars().next().is_some_and(|ch| ch > WS && self.options.is_allowed_delimiter(ch))
+  }
+
+  /// Returns `true` when the specified line is a comment line.
+  fn is_comment_line(&self, line: &str) -> bool {
+    self.options.is_comment_start(line.trim_start_matches([WS, TAB]), self.delimiter)
+  }
+
+  /// Returns `true` when the specified line begins a new node.
+  /// With escaping enabled, lines beginning with doubled delimiter or the delimiter followed by the comment marker are escaped content lines.
+  fn is_node_line(&self, line: &str) -> bool {
+    self.delimiter.is_some_and(|delimiter| {
+      let rest = line.trim_start_matches([WS, TAB]).strip_prefix(delimiter);
+      let escaped = |rest: &str| rest.starts_with(delimiter) || self.options.comment_marker().is_some_and(|marker| !marker.is_empty() && rest.starts_with(marker));
+      rest.is_some_and(|rest| !(self.options.escaping() && escaped(rest)))
+    })
+  }
+}
+
+/// State of checking the lines read from the input against the name and content length limits.
+#[derive(Copy, Clone)]
+struct LengthCheck {
+  /// The position of the next line to be checked.
+  position: Position,
+  /// Length of the node content checked so far, `None` before the first node.
+  len: Option<usize>,
+  /// Flag indicating that the node content was interrupted by a comment.
+  interrupted: bool,
+}
+
+impl Default for LengthCheck {
+  /// Creates the state of checking at the beginning of the input.
+  fn default() -> Self {
+    Self {
+      position: Position::new(1, 1, 0),
+      len: None,
+      interrupted: false,
+    }
+  }
+}
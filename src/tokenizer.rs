@@ -2,255 +2,668 @@
 
 use crate::defs::*;
 use crate::errors::*;
+use crate::options::ParseOptions;
+use crate::position::{Position, Span};
 use normalized_line_endings::{Annotated, AnnotatedChar, LineEnding, LF};
+use std::borrow::Cow;
+use std::collections::VecDeque;
 
 /// Tokenizes input text.
-pub fn tokenize(input: &str) -> Result<Vec<Token>> {
+pub fn tokenize(input: &str) -> Result<Vec<Token<'_>>> {
   Tokenizer::new(input).tokenize()
 }
 
 /// Tokens.
+///
+/// Names and contents borrow from the tokenized input whenever possible.
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub enum Token {
-  /// Node name token with delimiter character.
-  NodeName(String, char),
-  /// Node content token.
-  NodeContent(String),
-  /// Indentation token with indentation character.
-  Indentation(usize, char),
+pub enum Token<'a> {
+  /// Node name token with delimiter character and the span covering the delimiter and the name.
+  NodeName(Cow<'a, str>, char, Span),
+  /// Node content token with the span covering the content.
+  NodeContent(Cow<'a, str>, Span),
+  /// Indentation token with the original indentation text and the span covering the indentation.
+  Indentation(Cow<'a, str>, Span),
+  /// Preamble token with the span covering the text preceding the first node or comment,
+  /// including the byte order mark and leading blank lines.
+  Preamble(Cow<'a, str>, Span),
+  /// Comment token with the span covering the comment.
+  /// The comment covers the indentation and the marker of the comment line
+  /// and following blank lines up to the next node, comment or content line, including line endings.
+  Comment(Cow<'a, str>, Span),
+}
+
+impl Token<'_> {
+  /// Converts this token into a token that owns its text.
+  pub fn into_owned(self) -> Token<'static> {
+    match self {
+      Token::NodeName(name, delimiter, span) => Token::NodeName(Cow::Owned(name.into_owned()), delimiter, span),
+      Token::NodeContent(content, span) => Token::NodeContent(Cow::Owned(content.into_owned()), span),
+      Token::Indentation(indentation, span) => Token::Indentation(Cow::Owned(indentation.into_owned()), span),
+      Token::Comment(comment, span) => Token::Comment(Cow::Owned(comment.into_owned()), span),
+      Token::Preamble(preamble, span) => Token::Preamble(Cow::Owned(preamble.into_owned()), span),
+    }
+  }
+
+  /// Returns this token with the span relative to the beginning of a text starting at the specified origin,
+  /// converted to the span relative to the beginning of the whole input.
+  pub(crate) fn shifted(self, origin: Position) -> Self {
+    match self {
+      Token::NodeName(name, delimiter, span) => Token::NodeName(name, delimiter, span.shifted(origin)),
+      Token::NodeContent(content, span) => Token::NodeContent(content, span.shifted(origin)),
+      Token::Indentation(indentation, span) => Token::Indentation(indentation, span.shifted(origin)),
+      Token::Comment(comment, span) => Token::Comment(comment, span.shifted(origin)),
+      Token::Preamble(preamble, span) => Token::Preamble(preamble, span.shifted(origin)),
+    }
+  }
+
+  /// Returns the span of the token in the input.
+  pub fn span(&self) -> Span {
+    match self {
+      Token::NodeName(_, _, span) => *span,
+      Token::NodeContent(_, span) => *span,
+      Token::Indentation(_, span) => *span,
+      Token::Comment(_, span) => *span,
+      Token::Preamble(_, span) => *span,
+    }
+  }
 }
 
 /// Tokenizer state.
 #[derive(Copy, Clone)]
 enum TokenizerState {
-  /// At the beginning of the input.
+  /// At the beginning of the line before the first node.
   Start,
+  /// Inside the indentation of the line before the first node.
+  LeadingIndentation,
   /// At the beginning of the line.
   NewLine,
   /// Inside the indentation at the beginning of the line.
   Indentation,
+  /// After the delimiter at the beginning of the line,
+  /// either beginning the node name or escaping the delimiter in the node content.
+  Delimiter,
   /// Inside the node name.
   NodeName,
   /// Inside the node content.
   NodeContent,
+  /// Inside the comment line.
+  Comment,
+  /// Skipping the rest of the line before the first node, after reporting an error.
+  SkipLine,
 }
 
 /// Tokenizer.
+///
+/// Tokens are recognized lazily, one by one, while iterating over the tokenizer.
 pub struct Tokenizer<'a> {
   /// Current row position in processed input.
   row: usize,
   /// Current column position in processed content.
   column: usize,
+  /// Current byte offset in processed input.
+  offset: usize,
+  /// Length in bytes of currently processed character.
+  char_len: usize,
   /// Current tokenizing state.
   state: TokenizerState,
-  /// Input characters.
+  /// Input text.
   input: &'a str,
+  /// Input characters annotated with their positions.
+  chars: Box<dyn Iterator<Item = AnnotatedChar> + 'a>,
   /// Currently processed character.
   current_char: char,
   /// Last parsed line ending.
   line_ending: Option<LineEnding>,
-  /// The content of currently processed indentation.
-  indentation: String,
+  /// The position where currently processed indentation begins.
+  indentation_position: Position,
   /// Delimiter used in processed document.
   delimiter: char,
-  /// The name of currently processed node.
-  node_name: String,
-  /// The content of currently processed node.
-  node_content: String,
-  /// List of already processed tokens.
-  tokens: Vec<Token>,
+  /// The position of the delimiter of currently processed node.
+  node_name_position: Position,
+  /// The name of currently processed node, when it differs from the input
+  /// (only when unexpected characters were skipped while recovering).
+  node_name_owned: Option<String>,
+  /// The position where the content of currently processed node begins.
+  node_content_position: Position,
+  /// Flag indicating that the content of currently processed node was not consumed yet.
+  node_content_pending: bool,
+  /// Byte offsets of escaping delimiters in the content of currently processed node.
+  node_content_escapes: Vec<usize>,
+  /// The position where currently processed comment begins.
+  comment_position: Position,
+  /// Flag indicating that currently processed comment was not consumed yet.
+  comment_pending: bool,
+  /// Flag indicating that the text preceding the first node or comment was not consumed yet.
+  preamble_pending: bool,
+  /// Tokens already recognized, but not yet returned.
+  tokens: VecDeque<Token<'a>>,
+  /// Tokenizing options.
+  options: ParseOptions,
+  /// Flag indicating if the tokenizer continues after reporting an error.
+  recovering: bool,
+  /// List of errors reported while recovering.
+  errors: Vec<IdmlError>,
+  /// Flag indicating if the whole input was processed or an error was reported.
+  done: bool,
+}
+
+impl<'a> Iterator for Tokenizer<'a> {
+  type Item = Result<Token<'a>>;
+
+  /// Returns the next token recognized in the input.
+  fn next(&mut self) -> Option<Self::Item> {
+    while self.tokens.is_empty() && !self.done {
+      if let Err(err) = self.step() {
+        self.done = true;
+        return Some(Err(err));
+      }
+    }
+    self.tokens.pop_front().map(Ok)
+  }
 }
 
 impl<'a> Tokenizer<'a> {
   /// Creates a new instance of the tokenizer.
   pub fn new(input: &'a str) -> Self {
+    Self::with_options(input, &ParseOptions::default())
+  }
+
+  /// Creates a new instance of the tokenizer with the specified options.
+  pub fn with_options(input: &'a str, options: &ParseOptions) -> Self {
     Self {
       row: 1,
       column: 0,
+      offset: 0,
+      char_len: 0,
       state: TokenizerState::Start,
       input,
+      chars: Box::new(input.chars().annotated()),
       current_char: NULL,
       line_ending: None,
-      indentation: "".to_string(),
+      indentation_position: Position::default(),
       delimiter: NULL,
-      node_name: "".to_string(),
-      node_content: "".to_string(),
-      tokens: vec![],
+      node_name_position: Position::default(),
+      node_name_owned: None,
+      node_content_position: Position::default(),
+      node_content_pending: false,
+      node_content_escapes: vec![],
+      comment_position: Position::default(),
+      comment_pending: false,
+      preamble_pending: true,
+      tokens: VecDeque::new(),
+      options: options.clone(),
+      recovering: false,
+      errors: vec![],
+      done: false,
     }
   }
 
+  /// Creates a new instance of the tokenizer, continuing the tokenizing
+  /// of a document with already recognized delimiter.
+  /// The input must begin at the beginning of a line.
+  pub(crate) fn continued(input: &'a str, delimiter: char, options: &ParseOptions) -> Self {
+    let mut tokenizer = Self::with_options(input, options);
+    tokenizer.delimiter = delimiter;
+    tokenizer.state = TokenizerState::NewLine;
+    tokenizer.preamble_pending = false;
+    tokenizer
+  }
+
   /// Tokenizes the input text.
-  pub fn tokenize(mut self) -> Result<Vec<Token>> {
-    let mut chars = self.input.chars().annotated();
-    loop {
-      (self.current_char, self.line_ending) = if let Some(annotated_char) = chars.next() {
-        match annotated_char {
-          AnnotatedChar::Character(ch, row, column) => {
-            self.row = row;
-            self.column = column;
-            (ch, None)
+  pub fn tokenize(self) -> Result<Vec<Token<'a>>> {
+    self.collect()
+  }
+
+  /// Tokenizes the input text, continuing after errors.
+  /// Returns all tokens recognized in the input and all errors found.
+  /// Exceeding a limit set in options stops tokenizing.
+  pub fn tokenize_recovering(mut self) -> (Vec<Token<'a>>, Vec<IdmlError>) {
+    self.recovering = true;
+    let mut tokens = vec![];
+    while let Some(token) = self.next() {
+      match token {
+        Ok(token) => tokens.push(token),
+        Err(err) => self.errors.push(err),
+      }
+    }
+    (tokens, self.errors)
+  }
+
+  /// Processes the next character of the input.
+  fn step(&mut self) -> Result<()> {
+    self.offset += self.char_len;
+    (self.current_char, self.line_ending) = if let Some(annotated_char) = self.chars.next() {
+      match annotated_char {
+        AnnotatedChar::Character(ch, row, column) => {
+          self.row = row;
+          self.column = column;
+          self.char_len = ch.len_utf8();
+          (ch, None)
+        }
+        AnnotatedChar::LineEnding(line_ending, row, column) => {
+          self.row = row;
+          self.column = column;
+          self.char_len = line_ending.as_ref().len();
+          (LF, Some(line_ending))
+        }
+      }
+    } else {
+      // The end of input is positioned just after the last character.
+      if self.line_ending.is_some() {
+        self.row += 1;
+        self.column = 1;
+      } else {
+        self.column += 1;
+      }
+      self.char_len = 0;
+      (NULL, None)
+    };
+    self.check_input_len()?;
+    if let TokenizerState::Delimiter = self.state {
+      if self.options.escaping() && (self.is_delimiter(self.current_char) || self.is_comment_start()) && (self.node_content_pending || self.comment_pending) {
+        // Doubled delimiter or the delimiter followed by the comment marker at the beginning of the line
+        // escapes the delimiter or the marker in the node content, also when the content continues after a comment.
+        self.resume_node_content(self.indentation_position);
+        self.node_content_escapes.push(self.node_name_position.offset());
+        self.state = TokenizerState::NodeContent;
+        return Ok(());
+      }
+      // Single delimiter begins the node name, the current character is processed as a part of the name.
+      self.consume_pending(self.indentation_position)?;
+      self.consume_indentation()?;
+      self.state = TokenizerState::NodeName;
+    }
+    match self.state {
+      TokenizerState::Start => {
+        // Process the beginning of the document.
+        match self.current_char {
+          _ if self.options.preamble_allowed() && self.options.delimiters().is_none() => {
+            // Any line of the preamble could begin the first node, this is reported also when recovering.
+            return Err(err_preamble_without_delimiters(self.current_position()));
+          }
+          NULL => {
+            self.report(err_empty_input(self.current_position()))?;
+            self.done = true;
+          }
+          BOM if self.offset == 0 => {}
+          _ if self.is_comment_start() => {
+            self.begin_comment(self.current_position())?;
+          }
+          WS | TAB => {
+            self.indentation_position = self.current_position();
+            self.state = TokenizerState::LeadingIndentation;
           }
-          AnnotatedChar::LineEnding(line_ending, row, column) => {
-            self.row = row;
-            self.column = column;
-            (LF, Some(line_ending))
+          LF => {}
+          _ if self.options.preamble_allowed() && !self.is_allowed_first_delimiter(self.current_char) => {
+            self.state = TokenizerState::SkipLine;
+          }
+          ch if self.is_allowed_char(ch) && !self.options.is_allowed_delimiter(ch) => {
+            self.report(err_unexpected_delimiter(ch, self.current_position()))?;
+            self.state = TokenizerState::SkipLine;
+          }
+          ch if self.is_allowed_char(ch) => {
+            self.consume_pending(self.current_position())?;
+            self.delimiter = ch;
+            self
+              .tokens
+              .push_back(Token::Indentation(Cow::Borrowed(""), Span::new(self.current_position(), self.current_position())));
+            self.node_name_position = self.current_position();
+            self.state = TokenizerState::NodeName;
+          }
+          other => {
+            self.report(err_unexpected_character(other, self.current_position()))?;
+            self.state = TokenizerState::SkipLine;
           }
         }
-      } else {
-        (NULL, None)
-      };
-      match self.state {
-        TokenizerState::Start => {
-          // Process the beginning of the document.
-          match self.current_char {
-            NULL => return Err(err_empty_input()),
-            ch if self.is_allowed_char(ch) => {
-              self.delimiter = ch;
-              self.tokens.push(Token::Indentation(0, NULL));
-              self.state = TokenizerState::NodeName;
-            }
-            other => {
-              let ch = if other == LF { self.line_ending.unwrap_or(LineEnding::Lf).first() } else { other };
-              return Err(err_unexpected_character(ch, self.row, self.column));
-            }
+      }
+      TokenizerState::LeadingIndentation => {
+        // Process the indentation of the line before the first node, blank lines are skipped.
+        match self.current_char {
+          NULL => {
+            self.report(err_empty_input(self.current_position()))?;
+            self.done = true;
+          }
+          _ if self.is_comment_start() => {
+            self.begin_comment(self.indentation_position)?;
+          }
+          WS | TAB => {}
+          LF => self.state = TokenizerState::Start,
+          _ if self.options.preamble_allowed() => self.state = TokenizerState::SkipLine,
+          _ => {
+            // The first node must not be indented.
+            let ch = self.input[self.indentation_position.offset()..].chars().next().unwrap_or(WS);
+            self.report(err_unexpected_character(ch, self.indentation_position))?;
+            self.state = TokenizerState::SkipLine;
           }
         }
-        TokenizerState::NewLine => {
-          // Process the beginning of the line.
-          match self.current_char {
-            NULL => {
-              self.consume_node_content();
-              break;
-            }
-            ch if self.is_delimiter(ch) => {
-              self.consume_node_content();
-              self.tokens.push(Token::Indentation(0, NULL));
-              self.state = TokenizerState::NodeName;
-            }
-            WS => {
-              self.indentation.push(WS);
-              self.state = TokenizerState::Indentation;
-            }
-            TAB => {
-              self.indentation.push(TAB);
-              self.state = TokenizerState::Indentation;
-            }
-            LF => {
-              self.next_row();
-              self.node_content.push_str(self.line_ending.unwrap_or(LineEnding::Lf).as_ref());
-            }
-            other => {
-              self.node_content.push(other);
-              self.state = TokenizerState::NodeContent;
-            }
+      }
+      TokenizerState::SkipLine => {
+        // Skip the rest of the line that can not begin the document.
+        match self.current_char {
+          NULL if self.options.preamble_allowed() => {
+            self.report(err_empty_input(self.current_position()))?;
+            self.done = true;
           }
+          NULL => self.done = true,
+          LF => self.state = TokenizerState::Start,
+          _ => {}
         }
-        TokenizerState::NodeName => {
-          // Process the node name.
-          match self.current_char {
-            NULL => {
-              return Err(err_unexpected_end());
-            }
-            WS => {
-              self.consume_node_name();
-              self.node_content.push(WS);
-              self.state = TokenizerState::NodeContent;
-            }
-            TAB => {
-              self.consume_node_name();
-              self.node_content.push(TAB);
-              self.state = TokenizerState::NodeContent;
-            }
-            LF => {
-              self.next_row();
-              self.consume_node_name();
-              self.node_content.push_str(self.line_ending.unwrap_or(LineEnding::Lf).as_ref());
-              self.state = TokenizerState::NewLine;
+      }
+      TokenizerState::NewLine => {
+        // Process the beginning of the line.
+        match self.current_char {
+          NULL => {
+            self.consume_pending(self.current_position())?;
+            self.done = true;
+          }
+          _ if self.is_comment_start() => {
+            self.begin_comment(self.current_position())?;
+          }
+          ch if self.is_delimiter(ch) => {
+            self.indentation_position = self.current_position();
+            self.node_name_position = self.current_position();
+            self.state = TokenizerState::Delimiter;
+          }
+          WS | TAB => {
+            self.indentation_position = self.current_position();
+            self.state = TokenizerState::Indentation;
+          }
+          LF => {}
+          _ => {
+            self.resume_node_content(self.current_position());
+            self.state = TokenizerState::NodeContent;
+          }
+        }
+      }
+      TokenizerState::Delimiter => {}
+      TokenizerState::NodeName => {
+        // Process the node name.
+        match self.current_char {
+          NULL => {
+            self.report_missing_newline()?;
+            self.consume_node_name();
+            self.consume_pending(self.current_position())?;
+            self.done = true;
+          }
+          WS | TAB => {
+            self.consume_node_name();
+            self.state = TokenizerState::NodeContent;
+          }
+          LF => {
+            self.consume_node_name();
+            self.state = TokenizerState::NewLine;
+          }
+          ch if self.options.escaping() && self.node_name_slice().is_empty() && (self.is_delimiter(ch) || self.is_comment_start()) => {
+            // With escaping enabled, the name never begins with the delimiter nor the comment marker, also on the first line.
+            self.report(err_unexpected_character(ch, self.current_position()))?;
+            if self.node_name_owned.is_none() {
+              self.node_name_owned = Some(String::new());
             }
-            ch if self.is_allowed_char(ch) => {
-              self.node_name.push(self.current_char);
+          }
+          ch if self.is_allowed_char(ch) => {
+            self.check_name_len()?;
+            if let Some(node_name) = &mut self.node_name_owned {
+              node_name.push(ch);
             }
-            other => {
-              return Err(err_unexpected_character(other, self.row, self.column));
+          }
+          other => {
+            // The unexpected character is skipped when recovering.
+            self.report(err_unexpected_character(other, self.current_position()))?;
+            if self.node_name_owned.is_none() {
+              self.node_name_owned = Some(self.node_name_slice().to_string());
             }
           }
         }
-        TokenizerState::Indentation => {
-          // Process the indentation.
-          match self.current_char {
-            NULL => return Err(err_unexpected_end()),
-            ch if self.is_delimiter(ch) => {
-              self.consume_node_content();
-              self.consume_indentation()?;
-              self.state = TokenizerState::NodeName
-            }
-            WS => self.indentation.push(WS),
-            TAB => self.indentation.push(TAB),
-            ch => {
-              self.node_content.push_str(&self.indentation);
-              self.node_content.push(ch);
-              self.indentation.clear();
-              self.state = TokenizerState::NodeContent
-            }
+      }
+      TokenizerState::Indentation => {
+        // Process the indentation.
+        match self.current_char {
+          NULL => {
+            self.report_missing_newline()?;
+            self.consume_pending(self.current_position())?;
+            self.done = true;
+          }
+          _ if self.is_comment_start() => {
+            self.begin_comment(self.indentation_position)?;
+          }
+          ch if self.is_delimiter(ch) => {
+            self.node_name_position = self.current_position();
+            self.state = TokenizerState::Delimiter;
+          }
+          WS | TAB => {}
+          LF => self.state = TokenizerState::NewLine,
+          _ => {
+            self.resume_node_content(self.indentation_position);
+            self.state = TokenizerState::NodeContent;
           }
         }
-        TokenizerState::NodeContent => {
-          // Process the content.
-          match self.current_char {
-            NULL => return Err(err_unexpected_end()),
-            LF => {
-              self.next_row();
-              self.node_content.push_str(self.line_ending.unwrap_or(LineEnding::Lf).as_ref());
-              self.state = TokenizerState::NewLine
-            }
-            other => self.node_content.push(other),
+      }
+      TokenizerState::NodeContent => {
+        // Process the content.
+        match self.current_char {
+          NULL => {
+            self.report_missing_newline()?;
+            self.consume_pending(self.current_position())?;
+            self.done = true;
+          }
+          LF => self.state = TokenizerState::NewLine,
+          _ => {}
+        }
+      }
+      TokenizerState::Comment => {
+        // Process the comment line.
+        match self.current_char {
+          NULL if self.delimiter == NULL => {
+            self.report(err_empty_input(self.current_position()))?;
+            self.done = true;
           }
+          NULL => {
+            self.report_missing_newline()?;
+            self.consume_pending(self.current_position())?;
+            self.done = true;
+          }
+          LF if self.delimiter == NULL => self.state = TokenizerState::Start,
+          LF => self.state = TokenizerState::NewLine,
+          _ => {}
         }
       }
     }
-    Ok(self.tokens.clone())
+    Ok(())
   }
 
-  /// Consumes the indentation.
+  /// Consumes the indentation preceding the delimiter of currently processed node.
+  /// Mixed spaces and tabs are reported unless the tab stop is set,
+  /// the original indentation is kept in the token in both cases.
   fn consume_indentation(&mut self) -> Result<()> {
-    if self.indentation.chars().all(|ch| ch == WS) {
-      self.tokens.push(Token::Indentation(self.indentation.len(), WS));
-      self.indentation.clear();
-      Ok(())
-    } else if self.indentation.chars().all(|ch| ch == TAB) {
-      self.tokens.push(Token::Indentation(self.indentation.len(), TAB));
-      self.indentation.clear();
-      Ok(())
-    } else {
-      Err(err_inconsistent_indentation())
+    let indentation = &self.input[self.indentation_position.offset()..self.node_name_position.offset()];
+    if !is_uniform(indentation) && self.options.tab_stop().is_none() {
+      self.report(err_inconsistent_indentation(self.indentation_position))?;
     }
+    let span = Span::new(self.indentation_position, self.node_name_position);
+    self.tokens.push_back(Token::Indentation(Cow::Borrowed(indentation), span));
+    Ok(())
   }
 
   /// Consumes the node name.
+  /// The content of the node begins at the current character.
   fn consume_node_name(&mut self) {
-    self.tokens.push(Token::NodeName(self.node_name.clone(), self.delimiter));
-    self.node_name.clear();
+    let node_name = match self.node_name_owned.take() {
+      Some(node_name) => Cow::Owned(node_name),
+      None => Cow::Borrowed(self.node_name_slice()),
+    };
+    self
+      .tokens
+      .push_back(Token::NodeName(node_name, self.delimiter, Span::new(self.node_name_position, self.current_position())));
+    self.node_content_position = self.current_position();
+    self.node_content_pending = true;
+  }
+
+  /// Consumes the node content, ending at the specified position.
+  /// Nothing is consumed when there is no pending node content.
+  /// Escaping delimiters are removed from the content.
+  /// Content exceeding the length limit is reported also when recovering.
+  fn consume_node_content(&mut self, end: Position) -> Result<()> {
+    if !self.node_content_pending {
+      return Ok(());
+    }
+    self.node_content_pending = false;
+    let node_content = &self.input[self.node_content_position.offset()..end.offset()];
+    if let Some(limit) = self.options.max_content_len().filter(|limit| node_content.len() > *limit) {
+      let within = (0..=limit).rev().find(|index| node_content.is_char_boundary(*index)).unwrap_or_default();
+      return Err(err_content_too_long(limit, self.node_content_position.advanced(&node_content[..within])));
+    }
+    let node_content = if self.node_content_escapes.is_empty() {
+      Cow::Borrowed(node_content)
+    } else {
+      let mut unescaped = String::with_capacity(node_content.len());
+      let mut start = self.node_content_position.offset();
+      for escape in self.node_content_escapes.drain(..) {
+        unescaped.push_str(&self.input[start..escape]);
+        start = escape + self.delimiter.len_utf8();
+      }
+      unescaped.push_str(&self.input[start..end.offset()]);
+      Cow::Owned(unescaped)
+    };
+    self.tokens.push_back(Token::NodeContent(node_content, Span::new(self.node_content_position, end)));
+    Ok(())
   }
 
-  /// Consumes the node content.
-  fn consume_node_content(&mut self) {
-    self.tokens.push(Token::NodeContent(self.node_content.clone()));
-    self.node_content.clear();
+  /// Consumes the comment, ending at the specified position.
+  /// Nothing is consumed when there is no pending comment.
+  fn consume_comment(&mut self, end: Position) {
+    if !self.comment_pending {
+      return;
+    }
+    self.comment_pending = false;
+    let comment = &self.input[self.comment_position.offset()..end.offset()];
+    self.tokens.push_back(Token::Comment(Cow::Borrowed(comment), Span::new(self.comment_position, end)));
+  }
+
+  /// Consumes the preamble, ending at the specified position.
+  /// Nothing is consumed when the preamble was already consumed or is empty.
+  fn consume_preamble(&mut self, end: Position) {
+    if !self.preamble_pending {
+      return;
+    }
+    self.preamble_pending = false;
+    if end.offset() > 0 {
+      let preamble = &self.input[..end.offset()];
+      self.tokens.push_back(Token::Preamble(Cow::Borrowed(preamble), Span::new(Position::new(1, 1, 0), end)));
+    }
+  }
+
+  /// Consumes the pending preamble, node content or comment, ending at the specified position.
+  fn consume_pending(&mut self, end: Position) -> Result<()> {
+    self.consume_preamble(end);
+    self.consume_node_content(end)?;
+    self.consume_comment(end);
+    Ok(())
+  }
+
+  /// Begins the comment at the specified position, ending the pending node content or comment.
+  fn begin_comment(&mut self, position: Position) -> Result<()> {
+    self.consume_pending(position)?;
+    self.comment_position = position;
+    self.comment_pending = true;
+    self.state = TokenizerState::Comment;
+    Ok(())
+  }
+
+  /// Ends the pending comment at the beginning of the line at the specified position,
+  /// the node content interrupted by the comment continues from this line.
+  /// Nothing happens when there is no pending comment.
+  fn resume_node_content(&mut self, position: Position) {
+    if self.comment_pending {
+      self.consume_comment(position);
+      self.node_content_position = position;
+      self.node_content_pending = true;
+    }
+  }
+
+  /// Returns `true` when the comment marker begins at the current character, not beginning with the delimiter.
+  fn is_comment_start(&self) -> bool {
+    self
+      .options
+      .is_comment_start(&self.input[self.offset..], Some(self.delimiter).filter(|delimiter| *delimiter != NULL))
+  }
+
+  /// Returns the part of the input between the delimiter and the current character.
+  fn node_name_slice(&self) -> &'a str {
+    &self.input[self.node_name_position.offset() + self.delimiter.len_utf8()..self.offset]
+  }
+
+  /// Checks the length of the input, including the current character, against the limit set in options.
+  /// Exceeding the limit is reported also when recovering.
+  fn check_input_len(&self) -> Result<()> {
+    match self.options.max_input_len() {
+      Some(limit) if self.offset + self.char_len > limit => Err(err_input_too_long(limit, self.current_position())),
+      _ => Ok(()),
+    }
+  }
+
+  /// Checks the length of the node name, including the current character, against the limit set in options.
+  /// Exceeding the limit is reported also when recovering.
+  fn check_name_len(&self) -> Result<()> {
+    match self.options.max_name_len() {
+      Some(limit) if self.offset + self.char_len - self.node_name_position.offset() - self.delimiter.len_utf8() > limit => Err(err_name_too_long(limit, self.current_position())),
+      _ => Ok(()),
+    }
+  }
+
+  /// Reports the end of input in the middle of a line,
+  /// unless the final newline is not required by options.
+  fn report_missing_newline(&mut self) -> Result<()> {
+    if self.options.final_newline_required() {
+      self.report(err_unexpected_end(self.current_position()))?;
+    }
+    Ok(())
+  }
+
+  /// Reports an error.
+  /// When recovering, the error is recorded and tokenizing continues,
+  /// otherwise the error is returned.
+  fn report(&mut self, err: IdmlError) -> Result<()> {
+    if self.recovering {
+      self.errors.push(err);
+      Ok(())
+    } else {
+      Err(err)
+    }
+  }
+
+  /// Returns the position of the currently processed character.
+  fn current_position(&self) -> Position {
+    Position::new(self.row, self.column, self.offset)
   }
 
   /// Returns `true` when the specified character is allowed character.
   fn is_allowed_char(&self, ch: char) -> bool {
-    matches!(ch, '\u{0021}'..='\u{10FFFF}')
+    is_allowed_char(ch)
+  }
+
+  /// Returns `true` when the specified character may be the delimiter of the first node.
+  fn is_allowed_first_delimiter(&self, ch: char) -> bool {
+    self.is_allowed_char(ch) && self.options.is_allowed_delimiter(ch)
   }
 
   /// Returns `true` when the specified character is equal to recognized delimiter.
   fn is_delimiter(&self, ch: char) -> bool {
     ch == self.delimiter
   }
+}
 
-  /// Advances the counter to the next row.
-  fn next_row(&mut self) {
-    self.row += 1;
-    self.column = 0;
-  }
+/// Returns `true` when the specified character is allowed in delimiters and node names.
+pub(crate) fn is_allowed_char(ch: char) -> bool {
+  matches!(ch, '\u{0021}'..='\u{10FFFF}')
+}
+
+/// Returns the indentation character, the first character of the indentation, [NULL] when the indentation is empty.
+pub(crate) fn indent_char(indentation: &str) -> char {
+  indentation.chars().next().unwrap_or(NULL)
+}
+
+/// Returns `true` when the indentation, consisting of spaces and tabs, does not mix them.
+pub(crate) fn is_uniform(indentation: &str) -> bool {
+  !indentation.contains(if indent_char(indentation) == TAB { WS } else { TAB })
 }
@@ -0,0 +1,256 @@
+//! # Tree traversal implementation
+
+use crate::node::Node;
+use std::collections::VecDeque;
+
+/// Iterator over descendant nodes in depth-first pre-order.
+pub(crate) struct PreOrder<'b, 'a> {
+  /// Nodes whose children are being visited, with the index of the next child to visit.
+  stack: Vec<(&'b Node<'a>, usize)>,
+}
+
+impl<'b, 'a> PreOrder<'b, 'a> {
+  /// Creates an iterator over descendants of the specified node.
+  pub(crate) fn new(node: &'b Node<'a>) -> Self {
+    Self { stack: vec![(node, 0)] }
+  }
+}
+
+impl<'b, 'a> Iterator for PreOrder<'b, 'a> {
+  type Item = &'b Node<'a>;
+
+  /// Returns the next descendant node, a node precedes its children.
+  fn next(&mut self) -> Option<Self::Item> {
+    loop {
+      let (node, index) = self.stack.last_mut()?;
+      match node.child(*index) {
+        Some(child) => {
+          *index += 1;
+          self.stack.push((child, 0));
+          return Some(child);
+        }
+        None => {
+          self.stack.pop();
+        }
+      }
+    }
+  }
+}
+
+/// Iterator over descendant nodes in depth-first post-order.
+pub(crate) struct PostOrder<'b, 'a> {
+  /// Nodes whose children are being visited, with the index of the next child to visit.
+  stack: Vec<(&'b Node<'a>, usize)>,
+}
+
+impl<'b, 'a> PostOrder<'b, 'a> {
+  /// Creates an iterator over descendants of the specified node.
+  pub(crate) fn new(node: &'b Node<'a>) -> Self {
+    Self { stack: vec![(node, 0)] }
+  }
+}
+
+impl<'b, 'a> Iterator for PostOrder<'b, 'a> {
+  type Item = &'b Node<'a>;
+
+  /// Returns the next descendant node, a node follows its children.
+  fn next(&mut self) -> Option<Self::Item> {
+    loop {
+      let (node, index) = self.stack.last_mut()?;
+      match node.child(*index) {
+        Some(child) => {
+          *index += 1;
+          self.stack.push((child, 0));
+        }
+        None => {
+          let (node, _) = self.stack.pop()?;
+          // The node the traversal started from is not its own descendant.
+          return (!self.stack.is_empty()).then_some(node);
+        }
+      }
+    }
+  }
+}
+
+/// Iterator over descendant nodes in breadth-first order.
+pub(crate) struct BreadthFirst<'b, 'a> {
+  /// Nodes already reached, but not yet returned.
+  queue: VecDeque<&'b Node<'a>>,
+}
+
+impl<'b, 'a> BreadthFirst<'b, 'a> {
+  /// Creates an iterator over descendants of the specified node.
+  pub(crate) fn new(node: &'b Node<'a>) -> Self {
+    Self { queue: node.children().collect() }
+  }
+}
+
+impl<'b, 'a> Iterator for BreadthFirst<'b, 'a> {
+  type Item = &'b Node<'a>;
+
+  /// Returns the next descendant node, all nodes at a level precede nodes at the next level.
+  fn next(&mut self) -> Option<Self::Item> {
+    let node = self.queue.pop_front()?;
+    self.queue.extend(node.children());
+    Some(node)
+  }
+}
+
+/// Cursor navigating the tree below the node it was created for.
+///
+/// Nodes own their children without links to parent nodes,
+/// so the cursor keeps the chain of ancestors of the current node,
+/// allowing navigation to parent, ancestor and sibling nodes.
+/// The cursor never moves above the node it was created for.
+///
+/// ```
+/// use idml::parse;
+///
+/// let root = parse(".A\n  .B\n  .C\n    .D\n").unwrap();
+/// let mut cursor = root.cursor();
+/// assert!(cursor.goto_first_child() && cursor.goto_first_child());
+/// assert_eq!("B", cursor.node().name());
+/// assert_eq!("C", cursor.next_sibling().unwrap().name());
+/// assert!(cursor.goto_next_sibling() && cursor.goto_first_child());
+/// assert_eq!("D", cursor.node().name());
+/// let ancestors = cursor.ancestors().map(|node| node.name()).collect::<Vec<&str>>();
+/// assert_eq!(vec!["C", "A", "root"], ancestors);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Cursor<'b, 'a> {
+  /// The current node.
+  node: &'b Node<'a>,
+  /// Ancestors of the current node, each with the index of its child leading to the current node.
+  ancestors: Vec<(&'b Node<'a>, usize)>,
+}
+
+impl<'b, 'a> Cursor<'b, 'a> {
+  /// Creates a new cursor placed at the specified node.
+  pub fn new(node: &'b Node<'a>) -> Self {
+    Self { node, ancestors: vec![] }
+  }
+
+  /// Returns the current node.
+  pub fn node(&self) -> &'b Node<'a> {
+    self.node
+  }
+
+  /// Returns the number of ancestors of the current node, up to the node the cursor was created for.
+  pub fn depth(&self) -> usize {
+    self.ancestors.len()
+  }
+
+  /// Returns the index of the current node among its siblings,
+  /// `None` when the cursor is placed at the node it was created for.
+  pub fn index(&self) -> Option<usize> {
+    self.ancestors.last().map(|(_, index)| *index)
+  }
+
+  /// Returns the parent of the current node.
+  pub fn parent(&self) -> Option<&'b Node<'a>> {
+    self.ancestors.last().map(|(parent, _)| *parent)
+  }
+
+  /// Returns an iterator over ancestors of the current node, starting from the parent node.
+  pub fn ancestors(&self) -> impl Iterator<Item = &'b Node<'a>> + '_ {
+    self.ancestors.iter().rev().map(|(ancestor, _)| *ancestor)
+  }
+
+  /// Returns the sibling node following the current node.
+  pub fn next_sibling(&self) -> Option<&'b Node<'a>> {
+    let (parent, index) = self.ancestors.last()?;
+    parent.child(index + 1)
+  }
+
+  /// Returns the sibling node preceding the current node.
+  pub fn previous_sibling(&self) -> Option<&'b Node<'a>> {
+    let (parent, index) = self.ancestors.last()?;
+    parent.child(index.checked_sub(1)?)
+  }
+
+  /// Moves the cursor to the parent node, returns `false` when there is no parent node.
+  pub fn goto_parent(&mut self) -> bool {
+    match self.ancestors.pop() {
+      Some((parent, _)) => {
+        self.node = parent;
+        true
+      }
+      None => false,
+    }
+  }
+
+  /// Moves the cursor to the child node at the specified index, returns `false` when there is no such child node.
+  pub fn goto_child(&mut self, index: usize) -> bool {
+    match self.node.child(index) {
+      Some(child) => {
+        self.ancestors.push((self.node, index));
+        self.node = child;
+        true
+      }
+      None => false,
+    }
+  }
+
+  /// Moves the cursor to the first child node, returns `false` when there are no child nodes.
+  pub fn goto_first_child(&mut self) -> bool {
+    self.goto_child(0)
+  }
+
+  /// Moves the cursor to the last child node, returns `false` when there are no child nodes.
+  pub fn goto_last_child(&mut self) -> bool {
+    self.node.child_count() > 0 && self.goto_child(self.node.child_count() - 1)
+  }
+
+  /// Moves the cursor to the following sibling node, returns `false` when there is no following sibling node.
+  pub fn goto_next_sibling(&mut self) -> bool {
+    self.goto_sibling(|index| index.checked_add(1))
+  }
+
+  /// Moves the cursor to the preceding sibling node, returns `false` when there is no preceding sibling node.
+  pub fn goto_previous_sibling(&mut self) -> bool {
+    self.goto_sibling(|index| index.checked_sub(1))
+  }
+
+  /// Moves the cursor to the next node in depth-first pre-order, below the node the cursor was created for.
+  /// Returns `false` when all nodes were already visited, the cursor is then placed back at the node it was created for.
+  ///
+  /// ```
+  /// use idml::parse;
+  ///
+  /// let root = parse(".A\n  .B\n.C\n").unwrap();
+  /// let mut cursor = root.cursor();
+  /// let mut names = vec![];
+  /// while cursor.goto_next() {
+  ///   names.push(format!("{}/{}", cursor.parent().unwrap().name(), cursor.node().name()));
+  /// }
+  /// assert_eq!(vec!["root/A", "A/B", "root/C"], names);
+  /// ```
+  pub fn goto_next(&mut self) -> bool {
+    if self.goto_first_child() {
+      return true;
+    }
+    loop {
+      if self.goto_next_sibling() {
+        return true;
+      }
+      if !self.goto_parent() {
+        return false;
+      }
+    }
+  }
+
+  /// Moves the cursor to the sibling node at the index computed from the index of the current node.
+  fn goto_sibling(&mut self, f: impl FnOnce(usize) -> Option<usize>) -> bool {
+    let Some((parent, index)) = self.ancestors.last_mut() else {
+      return false;
+    };
+    match f(*index).and_then(|sibling| Some((sibling, parent.child(sibling)?))) {
+      Some((sibling, node)) => {
+        *index = sibling;
+        self.node = node;
+        true
+      }
+      None => false,
+    }
+  }
+}
@@ -0,0 +1,423 @@
+//! # Arena tree implementation
+
+use crate::defs::WS;
+use crate::errors::Result;
+use crate::node::{Node, DEFAULT_INDENT};
+use crate::position::Span;
+use std::borrow::Cow;
+
+/// Identifier of a node stored in a [Tree].
+///
+/// Identifiers stay valid as long as the tree exists, also when nodes are moved or removed,
+/// so they can be used as keys in tables kept next to the tree.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NodeId(usize);
+
+impl NodeId {
+  /// Returns the index of the node in the arena, nodes converted from [Node] are indexed in document order.
+  pub fn index(&self) -> usize {
+    self.0
+  }
+}
+
+/// View of a node stored in a [Tree].
+///
+/// Gives access to the data of the node, the related nodes are reached using the navigation methods of the tree.
+#[derive(Debug, Copy, Clone)]
+pub struct TreeNode<'t, 'a>(&'t Node<'a>);
+
+impl<'t> TreeNode<'t, '_> {
+  /// Returns the indentation level of the node.
+  pub fn level(&self) -> usize {
+    self.0.level()
+  }
+
+  /// Returns the number of characters of the original indentation preceding the delimiter.
+  pub fn indent(&self) -> usize {
+    self.0.indent()
+  }
+
+  /// Returns the original indentation character, see [Node::indent_char].
+  pub fn indent_char(&self) -> char {
+    self.0.indent_char()
+  }
+
+  /// Returns the delimiter of the node.
+  pub fn delimiter(&self) -> char {
+    self.0.delimiter()
+  }
+
+  /// Returns the node name.
+  pub fn name(&self) -> &'t str {
+    self.0.name()
+  }
+
+  /// Returns the node content, see [Node::content].
+  pub fn content(&self) -> &'t str {
+    self.0.content()
+  }
+
+  /// Returns the preamble, see [Node::preamble].
+  pub fn preamble(&self) -> &'t str {
+    self.0.preamble()
+  }
+
+  /// Returns the node text, the trimmed node content.
+  pub fn text(&self) -> &'t str {
+    self.0.text()
+  }
+
+  /// Returns an iterator over trimmed comments preceding the node, see [Node::comments].
+  pub fn comments(&self) -> impl Iterator<Item = &'t str> {
+    self.0.comments()
+  }
+
+  /// Returns the span of the node in the parsed input, including all descendant nodes.
+  pub fn span(&self) -> Span {
+    self.0.span()
+  }
+
+  /// Returns the span of the delimiter and the name of the node.
+  pub fn name_span(&self) -> Span {
+    self.0.name_span()
+  }
+
+  /// Returns the span of the node content.
+  pub fn content_span(&self) -> Span {
+    self.0.content_span()
+  }
+}
+
+/// Links of a node to its neighbours in the tree.
+#[derive(Debug, Copy, Clone, Default)]
+struct Links {
+  /// The parent node.
+  parent: Option<NodeId>,
+  /// The first child node.
+  first_child: Option<NodeId>,
+  /// The last child node.
+  last_child: Option<NodeId>,
+  /// The preceding sibling node.
+  previous_sibling: Option<NodeId>,
+  /// The following sibling node.
+  next_sibling: Option<NodeId>,
+}
+
+/// Document tree stored in an arena.
+///
+/// Nodes are referenced by [NodeId] handles and linked to their parent, child and sibling nodes,
+/// so navigating and editing the tree takes constant time per step, apart from adjusting
+/// the levels of moved nodes. Nodes in the arena hold no child nodes, the structure
+/// of the tree is kept only in the links. Detached nodes stay in the arena until the tree is dropped.
+///
+/// ```
+/// use idml::{parse, Node, Tree};
+///
+/// let root = parse(".A\n  .B\n.C\n").unwrap().into_root();
+/// let mut tree = Tree::from(root);
+/// let a = tree.first_child(tree.root()).unwrap();
+/// let c = tree.next_sibling(a).unwrap();
+/// let b = tree.first_child(a).unwrap();
+/// assert_eq!(Some(a), tree.parent(b));
+/// tree.append(c, b);
+/// let d = tree.add(Node::new('.', "D")?);
+/// tree.insert_after(c, d);
+/// assert_eq!(".A\n.C\n  .B\n.D\n", Node::from(tree).to_source());
+/// # Ok::<(), idml::IdmlError>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct Tree<'a> {
+  /// Nodes without child nodes, indexed by node identifiers.
+  nodes: Vec<Node<'a>>,
+  /// Links of nodes, indexed by node identifiers.
+  links: Vec<Links>,
+  /// The root node of the tree.
+  root: NodeId,
+}
+
+impl Default for Tree<'_> {
+  /// Creates a tree with an empty root node.
+  fn default() -> Self {
+    Self::from(Node::root())
+  }
+}
+
+impl<'a> From<Node<'a>> for Tree<'a> {
+  /// Converts the node and all its descendants into a tree, the node becomes the root of the tree.
+  fn from(node: Node<'a>) -> Self {
+    let mut tree = Self {
+      nodes: vec![],
+      links: vec![],
+      root: NodeId(0),
+    };
+    tree.root = tree.add(node);
+    tree
+  }
+}
+
+impl<'a> From<Tree<'a>> for Node<'a> {
+  /// Converts the tree into its root node, detached nodes are dropped.
+  fn from(tree: Tree<'a>) -> Self {
+    let mut nodes = tree.nodes.into_iter().map(Some).collect::<Vec<Option<Node<'a>>>>();
+    assemble(&tree.links, tree.root, |id| nodes[id.0].take().unwrap_or_else(Node::root))
+  }
+}
+
+impl<'a> Tree<'a> {
+  /// Creates a tree with an empty root node.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Returns the identifier of the root node.
+  pub fn root(&self) -> NodeId {
+    self.root
+  }
+
+  /// Returns the view of the node with the specified identifier, giving access to the data of the node only.
+  /// Use the navigation methods of the tree to reach related nodes,
+  /// or [to_node](Self::to_node) to get the node together with its descendants.
+  ///
+  /// # Panics
+  ///
+  /// Panics when the identifier does not belong to this tree.
+  pub fn node(&self, id: NodeId) -> TreeNode<'_, 'a> {
+    TreeNode(&self.nodes[id.0])
+  }
+
+  /// Sets the name of the node with the specified identifier, see [Node::set_name].
+  pub fn set_name(&mut self, id: NodeId, name: impl Into<Cow<'a, str>>) -> Result<()> {
+    self.nodes[id.0].set_name(name)
+  }
+
+  /// Sets the content of the node with the specified identifier, see [Node::set_content].
+  pub fn set_content(&mut self, id: NodeId, content: impl Into<Cow<'a, str>>) {
+    self.nodes[id.0].set_content(content);
+  }
+
+  /// Returns the parent of the specified node, `None` for the root node and detached nodes.
+  pub fn parent(&self, id: NodeId) -> Option<NodeId> {
+    self.links[id.0].parent
+  }
+
+  /// Returns the first child of the specified node.
+  pub fn first_child(&self, id: NodeId) -> Option<NodeId> {
+    self.links[id.0].first_child
+  }
+
+  /// Returns the last child of the specified node.
+  pub fn last_child(&self, id: NodeId) -> Option<NodeId> {
+    self.links[id.0].last_child
+  }
+
+  /// Returns the sibling node preceding the specified node.
+  pub fn previous_sibling(&self, id: NodeId) -> Option<NodeId> {
+    self.links[id.0].previous_sibling
+  }
+
+  /// Returns the sibling node following the specified node.
+  pub fn next_sibling(&self, id: NodeId) -> Option<NodeId> {
+    self.links[id.0].next_sibling
+  }
+
+  /// Returns an iterator over child nodes of the specified node.
+  pub fn children(&self, id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+    std::iter::successors(self.first_child(id), |child| self.next_sibling(*child))
+  }
+
+  /// Returns an iterator over ancestors of the specified node, starting from the parent node.
+  pub fn ancestors(&self, id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+    std::iter::successors(self.parent(id), |ancestor| self.parent(*ancestor))
+  }
+
+  /// Returns an iterator over descendants of the specified node in depth-first pre-order,
+  /// the node itself is not included.
+  pub fn descendants(&self, id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+    std::iter::successors(self.first_child(id), move |current| self.following(*current, id))
+  }
+
+  /// Adds the node and all its descendants to the arena, returns the identifier of the added node.
+  /// The added node is detached, it has to be appended or inserted to become a part of the tree.
+  pub fn add(&mut self, node: Node<'a>) -> NodeId {
+    let id = NodeId(self.nodes.len());
+    let mut stack = vec![(None, node)];
+    while let Some((parent, mut node)) = stack.pop() {
+      let children = node.take_children();
+      let parent = self.push(parent, node);
+      // Children are stacked in reverse order, so nodes are added to the arena in document order.
+      stack.extend(children.into_iter().rev().map(|child| (Some(parent), child)));
+    }
+    id
+  }
+
+  /// Appends the node as the last child of the parent node, detaching it from its current place.
+  /// The levels of the node and all its descendants are adjusted to the new place in the tree.
+  ///
+  /// # Panics
+  ///
+  /// Panics when the node is the root node or an ancestor of the parent node.
+  pub fn append(&mut self, parent: NodeId, id: NodeId) {
+    self.attach(id, parent, self.last_child(parent), None);
+  }
+
+  /// Prepends the node as the first child of the parent node, detaching it from its current place.
+  ///
+  /// # Panics
+  ///
+  /// Panics when the node is the root node or an ancestor of the parent node.
+  pub fn prepend(&mut self, parent: NodeId, id: NodeId) {
+    self.attach(id, parent, None, self.first_child(parent));
+  }
+
+  /// Inserts the node just before the sibling node, detaching it from its current place.
+  ///
+  /// # Panics
+  ///
+  /// Panics when the sibling node has no parent, or the node is the root node or an ancestor of the sibling node.
+  pub fn insert_before(&mut self, sibling: NodeId, id: NodeId) {
+    let parent = self.parent(sibling).expect("sibling node has no parent");
+    self.attach(id, parent, self.previous_sibling(sibling), Some(sibling));
+  }
+
+  /// Inserts the node just after the sibling node, detaching it from its current place.
+  ///
+  /// # Panics
+  ///
+  /// Panics when the sibling node has no parent, or the node is the root node or an ancestor of the sibling node.
+  pub fn insert_after(&mut self, sibling: NodeId, id: NodeId) {
+    let parent = self.parent(sibling).expect("sibling node has no parent");
+    self.attach(id, parent, Some(sibling), self.next_sibling(sibling));
+  }
+
+  /// Detaches the node together with all its descendants from the tree.
+  /// The detached node can be attached again, it is dropped otherwise when the tree is converted into [Node].
+  pub fn detach(&mut self, id: NodeId) {
+    let Links {
+      parent,
+      previous_sibling,
+      next_sibling,
+      ..
+    } = self.links[id.0];
+    let Some(parent) = parent else {
+      return;
+    };
+    match previous_sibling {
+      Some(previous) => self.links[previous.0].next_sibling = next_sibling,
+      None => self.links[parent.0].first_child = next_sibling,
+    }
+    match next_sibling {
+      Some(next) => self.links[next.0].previous_sibling = previous_sibling,
+      None => self.links[parent.0].last_child = previous_sibling,
+    }
+    let links = &mut self.links[id.0];
+    links.parent = None;
+    links.previous_sibling = None;
+    links.next_sibling = None;
+  }
+
+  /// Returns the node with the specified identifier together with all its descendants, as a new [Node].
+  pub fn to_node(&self, id: NodeId) -> Node<'a> {
+    assemble(&self.links, id, |id| self.nodes[id.0].clone())
+  }
+
+  /// Converts this tree into a tree that owns all its text, so it no longer borrows from the parsed input.
+  pub fn into_owned(self) -> Tree<'static> {
+    Tree {
+      nodes: self.nodes.into_iter().map(Node::into_owned).collect(),
+      links: self.links,
+      root: self.root,
+    }
+  }
+
+  /// Adds a single node to the arena as the last child of the specified parent node, without child nodes.
+  fn push(&mut self, parent: Option<NodeId>, node: Node<'a>) -> NodeId {
+    let id = NodeId(self.nodes.len());
+    self.nodes.push(node);
+    let mut links = Links { parent, ..Links::default() };
+    if let Some(parent) = parent {
+      links.previous_sibling = self.links[parent.0].last_child;
+      match links.previous_sibling {
+        Some(previous) => self.links[previous.0].next_sibling = Some(id),
+        None => self.links[parent.0].first_child = Some(id),
+      }
+      self.links[parent.0].last_child = Some(id);
+    }
+    self.links.push(links);
+    id
+  }
+
+  /// Attaches the node to the parent node, between the specified sibling nodes.
+  fn attach(&mut self, id: NodeId, parent: NodeId, previous: Option<NodeId>, next: Option<NodeId>) {
+    assert!(id != self.root, "root node cannot be attached");
+    assert!(
+      id != parent && self.ancestors(parent).all(|ancestor| ancestor != id),
+      "node cannot be attached below itself"
+    );
+    if previous == Some(id) || next == Some(id) {
+      // The node is already in place.
+      return;
+    }
+    self.detach(id);
+    let unit = self.indent_unit(parent).or_else(|| self.indent_unit(id)).unwrap_or((DEFAULT_INDENT, WS));
+    self.links[id.0] = Links {
+      parent: Some(parent),
+      previous_sibling: previous,
+      next_sibling: next,
+      ..self.links[id.0]
+    };
+    match previous {
+      Some(previous) => self.links[previous.0].next_sibling = Some(id),
+      None => self.links[parent.0].first_child = Some(id),
+    }
+    match next {
+      Some(next) => self.links[next.0].previous_sibling = Some(id),
+      None => self.links[parent.0].last_child = Some(id),
+    }
+    let mut stack = vec![(id, self.nodes[parent.0].level() + 1)];
+    while let Some((id, level)) = stack.pop() {
+      self.nodes[id.0].set_level(level, unit);
+      stack.extend(self.children(id).map(|child| (child, level + 1)).collect::<Vec<_>>());
+    }
+  }
+
+  /// Returns the indentation unit and indentation character used by the node or its nearest descendants.
+  fn indent_unit(&self, id: NodeId) -> Option<(usize, char)> {
+    let first_child = self.first_child(id);
+    [Some(id), first_child, first_child.and_then(|child| self.first_child(child))]
+      .into_iter()
+      .flatten()
+      .find_map(|id| self.nodes[id.0].own_indent_unit())
+  }
+
+  /// Returns the node following the current node in depth-first pre-order, not leaving the subtree of the specified node.
+  fn following(&self, mut current: NodeId, subtree: NodeId) -> Option<NodeId> {
+    if let Some(child) = self.first_child(current) {
+      return Some(child);
+    }
+    loop {
+      if let Some(next) = self.next_sibling(current) {
+        return Some(next);
+      }
+      current = self.parent(current).filter(|parent| *parent != subtree)?;
+    }
+  }
+}
+
+/// Assembles the node with the specified identifier and all its descendants into a [Node],
+/// taking the nodes without children from the specified function.
+fn assemble<'a>(links: &[Links], id: NodeId, mut take: impl FnMut(NodeId) -> Node<'a>) -> Node<'a> {
+  let mut stack = vec![(take(id), links[id.0].first_child)];
+  loop {
+    let (_, next) = stack.last_mut().unwrap();
+    if let Some(child) = *next {
+      *next = links[child.0].next_sibling;
+      stack.push((take(child), links[child.0].first_child));
+    } else {
+      let (node, _) = stack.pop().unwrap();
+      match stack.last_mut() {
+        Some((parent, _)) => parent.add_child(node),
+        None => return node,
+      }
+    }
+  }
+}
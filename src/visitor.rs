@@ -0,0 +1,147 @@
+//! # Tree visitor implementation
+
+use crate::node::Node;
+
+/// Decision returned by visitors, controlling how the walk continues.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum Visit {
+  /// Continue the walk, visiting child nodes of the entered node.
+  #[default]
+  Continue,
+  /// Continue the walk, but skip child nodes of the entered node.
+  /// When returned after leaving a node, the walk just continues.
+  SkipChildren,
+  /// Stop the walk, no more nodes are entered or left.
+  Stop,
+}
+
+/// Visitor of nodes in the tree, see [Node::walk].
+///
+/// Each node is entered before its child nodes and left after them,
+/// a node whose child nodes are skipped is left just after it is entered.
+/// When the walk is stopped, the nodes entered so far are not left.
+///
+/// ```
+/// use idml::{parse, Node, Visit, Visitor};
+///
+/// struct Secrets(Vec<String>);
+///
+/// impl Visitor for Secrets {
+///   fn enter_node(&mut self, node: &Node) -> Visit {
+///     match node.name() {
+///       "public" => Visit::SkipChildren,
+///       "password" => {
+///         self.0.push(node.text().to_string());
+///         Visit::Continue
+///       }
+///       _ => Visit::Continue,
+///     }
+///   }
+/// }
+///
+/// let root = parse(".public\n  .password a\n.private\n  .password b\n").unwrap();
+/// let mut secrets = Secrets(vec![]);
+/// assert!(root.walk(&mut secrets));
+/// assert_eq!(vec!["b"], secrets.0);
+/// ```
+pub trait Visitor {
+  /// Called when the node is entered, before visiting its child nodes.
+  fn enter_node(&mut self, _node: &Node) -> Visit {
+    Visit::Continue
+  }
+
+  /// Called when the node is left, after visiting its child nodes.
+  fn leave_node(&mut self, _node: &Node) -> Visit {
+    Visit::Continue
+  }
+}
+
+/// Visitor of nodes in the tree, allowed to modify visited nodes, see [Node::walk_mut].
+///
+/// Nodes are entered and left like with [Visitor]. Child nodes modified
+/// when entering a node are visited as modified, the nodes are left with all their child nodes.
+pub trait VisitorMut {
+  /// Called when the node is entered, before visiting its child nodes.
+  fn enter_node(&mut self, _node: &mut Node) -> Visit {
+    Visit::Continue
+  }
+
+  /// Called when the node is left, after visiting its child nodes.
+  fn leave_node(&mut self, _node: &mut Node) -> Visit {
+    Visit::Continue
+  }
+}
+
+/// Walks the node and all its descendants with the visitor, returns `false` when the walk was stopped.
+pub(crate) fn walk(node: &Node, visitor: &mut (impl Visitor + ?Sized)) -> bool {
+  match visitor.enter_node(node) {
+    Visit::Continue => {}
+    Visit::SkipChildren => return visitor.leave_node(node) != Visit::Stop,
+    Visit::Stop => return false,
+  }
+  let mut stack = vec![(node, 0)];
+  while let Some((node, index)) = stack.last_mut() {
+    match node.child(*index) {
+      Some(child) => {
+        *index += 1;
+        match visitor.enter_node(child) {
+          Visit::Continue => stack.push((child, 0)),
+          Visit::SkipChildren if visitor.leave_node(child) != Visit::Stop => {}
+          _ => return false,
+        }
+      }
+      None => {
+        if visitor.leave_node(node) == Visit::Stop {
+          return false;
+        }
+        stack.pop();
+      }
+    }
+  }
+  true
+}
+
+/// Walks the node and all its descendants with the visitor allowed to modify nodes,
+/// returns `false` when the walk was stopped.
+pub(crate) fn walk_mut(node: &mut Node, visitor: &mut (impl VisitorMut + ?Sized)) -> bool {
+  match visitor.enter_node(node) {
+    Visit::Continue => {}
+    Visit::SkipChildren => return visitor.leave_node(node) != Visit::Stop,
+    Visit::Stop => return false,
+  }
+  // Child nodes are taken out of the visited nodes and put back after the nodes are left,
+  // so each visited node is borrowed mutably only once.
+  let children = node.take_children();
+  let mut stack = vec![(std::mem::replace(node, Node::root()), children.into_iter(), vec![])];
+  let mut stopped = false;
+  loop {
+    let (_, pending, visited) = stack.last_mut().unwrap();
+    let next = if stopped { None } else { pending.next() };
+    match next {
+      Some(mut child) => match visitor.enter_node(&mut child) {
+        Visit::Continue => {
+          let children = child.take_children();
+          stack.push((child, children.into_iter(), vec![]));
+        }
+        visit => {
+          stopped = visit == Visit::Stop || visitor.leave_node(&mut child) == Visit::Stop;
+          visited.push(child);
+        }
+      },
+      None => {
+        let (mut parent, pending, mut visited) = stack.pop().unwrap();
+        // When the walk is stopped, child nodes not visited yet are put back unchanged.
+        visited.extend(pending);
+        parent.set_children(visited);
+        stopped = stopped || visitor.leave_node(&mut parent) == Visit::Stop;
+        match stack.last_mut() {
+          Some((_, _, visited)) => visited.push(parent),
+          None => {
+            *node = parent;
+            return !stopped;
+          }
+        }
+      }
+    }
+  }
+}
@@ -0,0 +1,52 @@
+use idml::{parse, tokenize, Document, Token};
+use std::borrow::Cow;
+
+fn is_within(input: &str, part: &str) -> bool {
+  let start = input.as_ptr() as usize;
+  let part_start = part.as_ptr() as usize;
+  part_start >= start && part_start + part.len() <= start + input.len()
+}
+
+#[test]
+fn _0001() {
+  // Tokens borrow names and contents from the input.
+  let input = ".A a\r\n  .B\n\n    b\n";
+  let tokens = tokenize(input).unwrap();
+  for token in &tokens {
+    match token {
+      Token::NodeName(name, _, _) => assert!(matches!(name, Cow::Borrowed(_))),
+      Token::NodeContent(content, _) => assert!(matches!(content, Cow::Borrowed(_))),
+      Token::Comment(comment, _) => assert!(matches!(comment, Cow::Borrowed(_))),
+      Token::Preamble(preamble, _) => assert!(matches!(preamble, Cow::Borrowed(_))),
+      Token::Indentation(..) => {}
+    }
+  }
+  assert_eq!(Token::NodeContent(Cow::Borrowed("\n\n    b\n"), tokens[5].span()), tokens[5]);
+}
+
+#[test]
+fn _0002() {
+  // Nodes borrow names and contents from the input.
+  let input = ".A a\r\n  .B\n\n    b\n";
+  let root = parse(input).unwrap();
+  let a = root.first_with_name("A").unwrap();
+  let b = a.first_with_name("B").unwrap();
+  assert!(is_within(input, a.name()));
+  assert!(is_within(input, a.content()));
+  assert!(is_within(input, b.name()));
+  assert!(is_within(input, b.content()));
+  assert_eq!(" a\r\n", a.content());
+  assert_eq!("\n\n    b\n", b.content());
+}
+
+#[test]
+fn _0003() {
+  // Owned nodes outlive the input.
+  let root: Document<'static> = {
+    let input = String::from(".A a\n  .B b\n");
+    parse(&input).unwrap().into_owned()
+  };
+  let a = root.first_with_name("A").unwrap();
+  assert_eq!("a", a.text());
+  assert_eq!("b", a.first_with_name("B").unwrap().text());
+}
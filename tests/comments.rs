@@ -0,0 +1,189 @@
+use idml::{events_with_options, parse, parse_reader_with_options, parse_with_options, Event, Node, ParseOptions, Token, Tokenizer, WS};
+
+fn options() -> ParseOptions {
+  ParseOptions::new().with_comment_marker("#").with_escaping(true)
+}
+
+#[test]
+fn _0001() {
+  // Comments are attached to the following node, not added as children.
+  let input = "# A sample idML file\n.company Engos Software\n.domains\n    # The first domain\n    . business analysts\n";
+  let root = parse_with_options(input, &options()).unwrap();
+  assert_eq!(vec!["company", "domains"], root.children().map(|node| node.name()).collect::<Vec<&str>>());
+  let company = root.first_with_name("company").unwrap();
+  assert_eq!(vec!["# A sample idML file"], company.comments().collect::<Vec<&str>>());
+  assert_eq!(" Engos Software\n", company.content());
+  let domains = root.first_with_name("domains").unwrap();
+  assert_eq!("\n", domains.content());
+  assert_eq!(0, domains.comments().count());
+  let domain = domains.children().next().unwrap();
+  assert_eq!(vec!["# The first domain"], domain.comments().collect::<Vec<&str>>());
+  assert_eq!("business analysts", domain.text());
+}
+
+#[test]
+fn _0002() {
+  // Comments are not recognized by default.
+  let input = ".A\n  # not a comment\n";
+  let root = parse(input).unwrap();
+  assert_eq!("\n  # not a comment\n", root.first_with_name("A").unwrap().content());
+  let root = parse_with_options(input, &options()).unwrap();
+  assert_eq!("\n", root.first_with_name("A").unwrap().content());
+}
+
+#[test]
+fn _0003() {
+  // Comment tokens cover the indentation, the marker and the line ending.
+  let input = ".A a\n  // first\n  // second\n  .B\n";
+  let tokens = Tokenizer::with_options(input, &ParseOptions::new().with_comment_marker("//"))
+    .collect::<Result<Vec<Token>, _>>()
+    .unwrap();
+  let comments = tokens
+    .iter()
+    .filter_map(|token| match token {
+      Token::Comment(comment, span) => Some((comment.as_ref(), span.start().offset(), span.end().offset())),
+      _ => None,
+    })
+    .collect::<Vec<_>>();
+  assert_eq!(vec![("  // first\n", 5, 16), ("  // second\n", 16, 28)], comments);
+}
+
+#[test]
+fn _0004() {
+  // Comments following the last node are attached to the root.
+  let input = ".A\n# trailing\n";
+  let root = parse_with_options(input, &options()).unwrap();
+  assert_eq!(vec!["# trailing"], root.comments().collect::<Vec<&str>>());
+  assert_eq!(0, root.first_with_name("A").unwrap().comments().count());
+}
+
+#[test]
+fn _0005() {
+  // Comments survive formatting and are reproduced in the source.
+  let input = "# header\n.A\n  # about B\n\n  .B b\n    # about C\n    .C\n# trailing\n";
+  let root = parse_with_options(input, &options()).unwrap();
+  assert_eq!(input, root.to_source());
+  let expected = "# header\n.A\n    # about B\n\n    .B b\n        # about C\n        .C\n# trailing\n";
+  let document = root.document(4, WS);
+  assert_eq!(expected, document);
+  assert_eq!(document, parse_with_options(&document, &options()).unwrap().document(4, WS));
+}
+
+#[test]
+fn _0006() {
+  // Comments are reported as events.
+  let input = ".A\n  # c\n  .B\n";
+  let events = events_with_options(input, &options()).map(|event| event.unwrap()).collect::<Vec<Event>>();
+  assert!(matches!(&events[2], Event::Comment(comment, _) if comment == "  # c\n"));
+  assert!(matches!(&events[3], Event::StartNode { name, .. } if name == "B"));
+}
+
+#[test]
+fn _0007() {
+  // Comments in a stream.
+  let input = "# header\n# more\n.A\n  # about B\n  .B\n";
+  let root = parse_reader_with_options(input.as_bytes(), &options()).unwrap();
+  let a = root.first_with_name("A").unwrap();
+  assert_eq!(vec!["# header", "# more"], a.comments().collect::<Vec<&str>>());
+  assert_eq!(vec!["# about B"], a.first_with_name("B").unwrap().comments().collect::<Vec<&str>>());
+  assert_eq!(input, root.to_source());
+}
+
+#[test]
+fn _0008() {
+  // Document containing only comments is empty.
+  assert_eq!("empty input", parse_with_options("# only comment\n", &options()).unwrap_err().to_string());
+}
+
+#[test]
+fn _0009() {
+  // Comment lines interrupt the content, the content continues after the comment.
+  let input = ".A first\n# note\nsecond line\n.B\n";
+  let root = parse_with_options(input, &options()).unwrap();
+  let a = root.first_with_name("A").unwrap();
+  assert_eq!(" first\nsecond line\n", a.content());
+  assert_eq!(0, root.first_with_name("B").unwrap().comments().count());
+  assert_eq!(input, root.to_source());
+  assert_eq!(input, parse_reader_with_options(input.as_bytes(), &options()).unwrap().to_source());
+}
+
+#[test]
+fn _0010() {
+  // Interrupted content is reported in events following the comment.
+  let input = ".A\n  # c\n\n  more\n  .B\n";
+  let events = events_with_options(input, &options()).map(|event| event.unwrap()).collect::<Vec<Event>>();
+  assert!(matches!(&events[1], Event::Content(content, _) if content == "\n"));
+  assert!(matches!(&events[2], Event::Comment(comment, _) if comment == "  # c\n\n"));
+  assert!(matches!(&events[3], Event::Content(content, _) if content == "  more\n"));
+  let root = parse_with_options(input, &options()).unwrap();
+  assert_eq!("\n  more\n", root.first_with_name("A").unwrap().content());
+  assert_eq!(input, root.to_source());
+  assert_eq!(".A\n# c\n\n  more\n  .B\n", root.document(2, WS));
+}
+
+#[test]
+fn _0011() {
+  // Doubled delimiter escapes the delimiter in the content continued after a comment.
+  let input = ".A\n# c\n..x\n";
+  let root = parse_with_options(input, &options()).unwrap();
+  let a = root.first_with_name("A").unwrap();
+  assert_eq!(0, a.child_count());
+  assert_eq!("\n.x\n", a.content());
+  assert_eq!(input, root.to_source());
+  assert_eq!(input, parse_reader_with_options(input.as_bytes(), &options()).unwrap().to_source());
+}
+
+#[test]
+fn _0012() {
+  // Content lines beginning with the comment marker are escaped with the delimiter.
+  let input = ".A\n.# not a comment\n  .#indented\n";
+  let root = parse_with_options(input, &options()).unwrap();
+  assert_eq!("\n# not a comment\n  #indented\n", root.first_with_name("A").unwrap().content());
+  assert_eq!(input, root.to_source());
+  assert_eq!(input, parse_reader_with_options(input.as_bytes(), &options()).unwrap().to_source());
+}
+
+#[test]
+fn _0013() {
+  // Content set on a parsed node survives formatting.
+  let mut root = parse_with_options(".A\n.B\n", &options()).unwrap();
+  root.first_with_name_mut("A").unwrap().set_content("x\n# y");
+  assert_eq!(".A x\n.# y\n.B\n", root.to_source());
+  for source in [root.to_source(), root.document(2, WS)] {
+    let reparsed = parse_with_options(&source, &options()).unwrap();
+    assert_eq!(" x\n# y\n", reparsed.first_with_name("A").unwrap().content());
+  }
+}
+
+#[test]
+fn _0014() {
+  // Nodes moved into a tree without comment marker keep escaping their comment-like content.
+  let mut document = parse_with_options(".A a\n.#x\n", &options()).unwrap();
+  let a = document.remove_child(0);
+  let mut root = Node::root();
+  root.push_child(a).unwrap();
+  assert_eq!(".A a\n.#x\n", root.to_source());
+  for source in [root.to_source(), root.document(2, WS)] {
+    let reparsed = parse_with_options(&source, &options()).unwrap();
+    assert_eq!(" a\n#x\n", reparsed.first_with_name("A").unwrap().content());
+  }
+}
+
+#[test]
+fn _0015() {
+  // The delimiter is checked before the comment marker.
+  let options = ParseOptions::new().with_comment_marker(".");
+  let input = "-A\n.x\n";
+  let root = parse_with_options(input, &options).unwrap();
+  assert_eq!("\n", root.first_with_name("A").unwrap().content());
+  assert_eq!(input, root.to_source());
+  let input = ".A a\n.B\n";
+  let options = ParseOptions::new().with_delimiter('.').with_comment_marker(".");
+  let root = parse_with_options(input, &options).unwrap();
+  assert_eq!(vec!["A", "B"], root.children().map(|node| node.name()).collect::<Vec<&str>>());
+  assert_eq!(input, root.to_source());
+  assert_eq!(input, parse_reader_with_options(input.as_bytes(), &options).unwrap().to_source());
+  let options = ParseOptions::new().with_comment_marker("//").with_escaping(true);
+  let root = parse_with_options("// comment\n/A a\n// b\n", &options).unwrap();
+  assert_eq!(" a\n/ b\n", root.first_with_name("A").unwrap().content());
+}
@@ -0,0 +1,23 @@
+//! # Helpers shared by tests
+//!
+//! Included with the `path` attribute by each test file using the helpers,
+//! so they are available also when the test file is compiled as a separate test target.
+
+#![allow(dead_code)]
+
+use idml::{Node, Position, Span};
+
+/// Returns names and levels of all descendant nodes in document order.
+pub fn levels<'a>(node: &'a Node) -> Vec<(&'a str, usize)> {
+  node.descendants().map(|node| (node.name(), node.level())).collect()
+}
+
+/// Returns texts of the specified nodes.
+pub fn texts<'a>(nodes: impl Iterator<Item = &'a Node<'a>>) -> Vec<&'a str> {
+  nodes.map(|node| node.text()).collect()
+}
+
+/// Creates a span from the rows, columns and offsets of its start and end.
+pub fn span(start_row: usize, start_column: usize, start_offset: usize, end_row: usize, end_column: usize, end_offset: usize) -> Span {
+  Span::new(Position::new(start_row, start_column, start_offset), Position::new(end_row, end_column, end_offset))
+}
@@ -0,0 +1,99 @@
+#[path = "common/mod.rs"]
+mod common;
+
+use common::levels;
+use idml::{detect_indent, detect_indent_with_options, events_with_options, parse_recovering_with_options, parse_with_options, ErrorKind, LevelCheck, ParseOptions, TAB, WS};
+
+fn options() -> ParseOptions {
+  ParseOptions::new().with_indent_detection(true)
+}
+
+#[test]
+fn _0001() {
+  // The most common step is the indentation unit.
+  assert_eq!(Some((2, WS)), detect_indent(".A\n  .B\n    .C\n  .D\n    .E\n        .F\n").unwrap());
+  assert_eq!(Some((4, WS)), detect_indent(".A\n    .B\n        .C\n    .D\n").unwrap());
+  assert_eq!(Some((1, TAB)), detect_indent(".A\n\t.B\n\t\t.C\n").unwrap());
+  assert_eq!(None, detect_indent(".A a\n.B\n  b\n").unwrap());
+}
+
+#[test]
+fn _0002() {
+  // Equally common steps, the smaller one is chosen.
+  assert_eq!(Some((2, WS)), detect_indent(".A\n    .B\n      .C\n").unwrap());
+}
+
+#[test]
+fn _0003() {
+  // Greatest common divisor, when it is one of the steps.
+  assert_eq!(Some((2, WS)), detect_indent(".A\n    .B\n        .C\n          .D\n    .E\n      .F\n").unwrap());
+}
+
+#[test]
+fn _0004() {
+  // Genuinely inconsistent indentation.
+  let err = detect_indent(".A\n    .B\n        .C\n  .D\n").unwrap_err();
+  assert_eq!("malformed indentation 2, expected multiplication of 4 at row 4 and column 1", err.to_string());
+  let err = detect_indent(".A\n  .B\n\t\t.C\n").unwrap_err();
+  assert_eq!(&ErrorKind::InconsistentIndentation, err.kind());
+}
+
+#[test]
+fn _0005() {
+  // Detection with the tab stop reports the unit in columns.
+  let options = ParseOptions::new().with_tab_stop(4);
+  assert_eq!(Some((4, TAB)), detect_indent_with_options(".A\n\t.B\n    .C\n", &options).unwrap());
+}
+
+#[test]
+fn _0006() {
+  // Doubly indented first line is reported, instead of the following lines.
+  let input = ".A\n    .B\n      .C\n";
+  let err = parse_with_options(input, &options()).unwrap_err();
+  assert_eq!("indentation jumps from level 1 to 3 at row 2 and column 1", err.to_string());
+  let err = parse_with_options(input, &ParseOptions::new()).unwrap_err();
+  assert_eq!("malformed indentation 6, expected multiplication of 4 at row 3 and column 1", err.to_string());
+}
+
+#[test]
+fn _0007() {
+  // Detected unit with lenient level checking.
+  let input = ".A\n    .B\n      .C\n    .D\n  .E\n";
+  let root = parse_with_options(input, &options().with_level_check(LevelCheck::Lenient)).unwrap();
+  assert_eq!(vec![("A", 1), ("B", 2), ("C", 3), ("D", 3), ("E", 2)], levels(&root));
+}
+
+#[test]
+fn _0008() {
+  // Fixed indentation takes precedence over the detection.
+  let input = ".A\n    .B\n      .C\n";
+  let err = parse_with_options(input, &options().with_indent(4, WS)).unwrap_err();
+  assert_eq!("malformed indentation 6, expected multiplication of 4 at row 3 and column 1", err.to_string());
+}
+
+#[test]
+fn _0009() {
+  // Inconsistent indentation is reported before any event, and while recovering.
+  let input = ".A\n    .B\n        .C\n  .D\n";
+  let first = events_with_options(input, &options()).next().unwrap().unwrap_err();
+  assert_eq!("malformed indentation 2, expected multiplication of 4 at row 4 and column 1", first.to_string());
+  let (root, errors) = parse_recovering_with_options(input, &options());
+  let messages = errors.iter().map(|err| err.to_string()).collect::<Vec<String>>();
+  assert_eq!(
+    vec![
+      "indentation jumps from level 1 to 3 at row 2 and column 1",
+      "malformed indentation 2, expected multiplication of 4 at row 4 and column 1"
+    ],
+    messages
+  );
+  assert_eq!(2, root.first_with_name("A").unwrap().child_count());
+}
+
+#[test]
+fn _0010() {
+  // Greatest common divisor, when the indentation decreases by this step.
+  let input = ".A\n    .B\n  .C\n";
+  assert_eq!(Some((2, WS)), detect_indent(input).unwrap());
+  let root = parse_with_options(input, &options().with_level_check(LevelCheck::Lenient)).unwrap();
+  assert_eq!(vec![("A", 1), ("B", 2), ("C", 2)], levels(&root));
+}
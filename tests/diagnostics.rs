@@ -0,0 +1,68 @@
+use idml::{parse, parse_with_options, Diagnostic, ParseOptions, Style};
+
+#[test]
+fn _0001() {
+  let input = ".A\n    .B\n    .C\n      .D\n";
+  let err = parse(input).unwrap_err();
+  let expected = r#"error: malformed indentation 6, expected multiplication of 4
+ --> config.idml:4:1
+  |
+2 |     .B
+  | ---- indentation 4 set here
+4 |       .D
+  | ^^^^^^
+  |
+  = hint: indentation 6 is not a multiple of 4 set on line 2
+"#;
+  assert_eq!(expected, Diagnostic::from(&err).render("config.idml", input, Style::Plain));
+}
+
+#[test]
+fn _0002() {
+  let input = ".A\n\t.B\u{1}\n";
+  let err = parse(input).unwrap_err();
+  let expected = "error: unexpected character: '\u{1}' 0x01
+ --> a.idml:2:4
+  |
+2 | \t.B\u{1}
+  | \t  ^
+  |
+";
+  assert_eq!(expected, Diagnostic::from(&err).render("a.idml", input, Style::Plain));
+}
+
+#[test]
+fn _0003() {
+  let input = ".server\n  .host localhost\n  .port 80800\n";
+  let root = parse(input).unwrap();
+  let port = root.first_with_name("server").unwrap().first_with_name("port").unwrap();
+  let diagnostic = Diagnostic::new("invalid port", port.content_span())
+    .with_label("port number out of range")
+    .with_hint("port must be in range 0..=65535");
+  let expected = r#"error: invalid port
+ --> config.idml:3:8
+  |
+3 |   .port 80800
+  |        ^^^^^^ port number out of range
+  |
+  = hint: port must be in range 0..=65535
+"#;
+  assert_eq!(expected, diagnostic.render("config.idml", input, Style::Plain));
+  assert_eq!("invalid port", diagnostic.message());
+  assert_eq!(Some("port must be in range 0..=65535"), diagnostic.hint());
+}
+
+#[test]
+fn _0004() {
+  let input = ".A";
+  let err = parse_with_options(input, &ParseOptions::new().with_final_newline_required(true)).unwrap_err();
+  let expected = "\u{1b}[1;31merror\u{1b}[0m\u{1b}[1m: unexpected end of input\u{1b}[0m
+ \u{1b}[1;34m-->\u{1b}[0m b.idml:1:3
+  \u{1b}[1;34m|\u{1b}[0m
+\u{1b}[1;34m1\u{1b}[0m \u{1b}[1;34m|\u{1b}[0m .A
+  \u{1b}[1;34m|\u{1b}[0m   \u{1b}[1;31m^\u{1b}[0m
+  \u{1b}[1;34m|\u{1b}[0m
+  \u{1b}[1;36m= hint:\u{1b}[0m the last line of the document must end with a newline
+";
+  assert_eq!(expected, Diagnostic::from(&err).render("b.idml", input, Style::Ansi));
+}
@@ -0,0 +1,81 @@
+use idml::{parse, parse_reader, parse_recovering, parse_with_options, LineEnding, Node, ParseOptions, TAB, WS};
+
+#[test]
+fn _0001() {
+  // Detected delimiter, indentation and line ending.
+  let document = parse("-config\r\n\t-name a\r\n").unwrap();
+  assert_eq!(Some('-'), document.delimiter());
+  assert_eq!(Some((1, TAB)), document.indent());
+  assert_eq!(Some(LineEnding::CrLf), document.line_ending());
+  assert_eq!("\r\n", document.line_ending().unwrap().as_str());
+}
+
+#[test]
+fn _0002() {
+  // Document without indented nodes and line endings.
+  let document = parse(".A a").unwrap();
+  assert_eq!(Some('.'), document.delimiter());
+  assert_eq!(None, document.indent());
+  assert_eq!(None, document.line_ending());
+}
+
+#[test]
+fn _0003() {
+  // Line ending in the preamble, comments and content.
+  assert_eq!(Some(LineEnding::Cr), parse("\r.A\n").unwrap().line_ending());
+  let options = ParseOptions::new().with_comment_marker("#");
+  assert_eq!(Some(LineEnding::CrLf), parse_with_options("# c\r\n.A\n", &options).unwrap().line_ending());
+  assert_eq!(Some(LineEnding::Lf), parse(".A a\n.B\r\n").unwrap().line_ending());
+}
+
+#[test]
+fn _0004() {
+  // Indentation from options and detected from all lines.
+  let options = ParseOptions::new().with_indent(4, WS);
+  assert_eq!(Some((4, WS)), parse_with_options(".A\n", &options).unwrap().indent());
+  let options = ParseOptions::new().with_indent_detection(true);
+  assert_eq!(Some((2, WS)), parse_with_options(".A\n  .B\n    .C\n", &options).unwrap().indent());
+}
+
+#[test]
+fn _0005() {
+  // Source text and the root node.
+  let input = "\u{FEFF}.A a\n  .B b\n";
+  let document = parse(input).unwrap();
+  assert_eq!(Some(input), document.source());
+  assert_eq!(input, document.to_source());
+  assert_eq!(input.len(), document.span().end().offset());
+  assert_eq!("\u{FEFF}", document.root().content());
+  let root: Node = document.into_root();
+  assert_eq!(1, root.child_count());
+}
+
+#[test]
+fn _0006() {
+  // Source text is not retained when reading from a stream.
+  let document = parse_reader(".A\n  .B\n".as_bytes()).unwrap();
+  assert_eq!(None, document.source());
+  assert_eq!(Some('.'), document.delimiter());
+  assert_eq!(Some((2, WS)), document.indent());
+  assert_eq!(Some(LineEnding::Lf), document.line_ending());
+}
+
+#[test]
+fn _0007() {
+  // Owned document keeps the source text.
+  let document = {
+    let input = String::from(".A\n    .B\n");
+    parse(&input).unwrap().into_owned()
+  };
+  assert_eq!(Some(".A\n    .B\n"), document.source());
+  assert_eq!(Some((4, WS)), document.indent());
+}
+
+#[test]
+fn _0008() {
+  // Best-effort document when recovering.
+  let (document, errors) = parse_recovering(".A\n  .B\n   .C\n");
+  assert_eq!(1, errors.len());
+  assert_eq!(Some((2, WS)), document.indent());
+  assert_eq!(Some('.'), document.delimiter());
+}
@@ -0,0 +1,189 @@
+#[path = "common/mod.rs"]
+mod common;
+
+use common::levels;
+use idml::{parse, parse_with_options, ErrorKind, Node, ParseOptions, TAB, WS};
+
+#[test]
+fn _0001() {
+  // Building a tree from code.
+  let mut root = Node::root();
+  let mut company = Node::new('.', "company").unwrap();
+  company.set_content("Engos Software");
+  let mut domain = Node::new('.', "domain").unwrap();
+  domain.push_child(Node::new('.', "analysts").unwrap()).unwrap();
+  company.push_child(domain).unwrap();
+  root.push_child(company).unwrap();
+  root.push_child(Node::new('.', "version").unwrap()).unwrap();
+  let expected = ".company Engos Software\n  .domain\n    .analysts\n.version\n";
+  assert_eq!(expected, root.to_source());
+  assert_eq!(vec![("company", 1), ("domain", 2), ("analysts", 3), ("version", 1)], levels(&root));
+  assert_eq!(expected, parse(expected).unwrap().to_source());
+}
+
+#[test]
+fn _0002() {
+  // Content is separated from the name and ends with a newline.
+  let mut node = Node::new('-', "A").unwrap();
+  assert_eq!("\n", node.content());
+  node.set_content("a");
+  assert_eq!(" a\n", node.content());
+  assert_eq!("a", node.text());
+  node.set_content("\tb\r\n");
+  assert_eq!("\tb\r\n", node.content());
+  node.set_content("\n-c\n");
+  assert_eq!("-A\n--c\n", node.to_source());
+}
+
+#[test]
+fn _0003() {
+  // Edits change only the edited lines.
+  let input = "-A a\r\n    -B b\r\n\r\n    -C c\r\n-D d\r\n";
+  let mut document = parse(input).unwrap();
+  let a = document.first_with_name_mut("A").unwrap();
+  a.first_with_name_mut("B").unwrap().set_name("b").unwrap();
+  a.children_mut().last().unwrap().set_content(" changed\r\n");
+  a.push_child(Node::new('-', "E").unwrap()).unwrap();
+  assert_eq!("-A a\r\n    -b b\r\n\r\n    -C changed\r\n    -E\n-D d\r\n", document.to_source());
+}
+
+#[test]
+fn _0004() {
+  // Moved subtrees are indented at the new level.
+  let mut root = parse(".A\n\t.B\n\t\t.C\n.D\n").unwrap().into_root();
+  let b = root.first_with_name_mut("A").unwrap().remove_child(0);
+  root.first_with_name_mut("D").unwrap().push_child(b.clone()).unwrap();
+  root.insert_child(0, b).unwrap();
+  assert_eq!(".B\n\t.C\n.A\n.D\n\t.B\n\t\t.C\n", root.to_source());
+  assert_eq!(vec![("B", 1), ("C", 2), ("A", 1), ("D", 1), ("B", 2), ("C", 3)], levels(&root));
+  let c = root.children().next().unwrap().children().next().unwrap();
+  assert_eq!((1, TAB), (c.indent(), c.indent_char()));
+}
+
+#[test]
+fn _0005() {
+  // Retaining and removing child nodes.
+  let mut root = parse(".A\n.B\n.C\n.D\n").unwrap().into_root();
+  root.retain(|node| node.name() != "B");
+  let removed = root.remove_child(1);
+  assert_eq!("C", removed.name());
+  assert_eq!(".A\n.D\n", root.to_source());
+}
+
+#[test]
+fn _0006() {
+  // Added nodes follow the indentation of the document.
+  let mut document = parse(".A\n    .B\n").unwrap();
+  let mut c = Node::new('.', "C").unwrap();
+  c.push_child(Node::new('.', "D").unwrap()).unwrap();
+  document.first_with_name_mut("A").unwrap().push_child(c).unwrap();
+  assert_eq!(".A\n    .B\n    .C\n        .D\n", document.to_source());
+  assert_eq!(".A\n  .B\n  .C\n    .D\n", document.document(2, WS));
+}
+
+#[test]
+fn _0007() {
+  // Root content is the preamble, set as is.
+  let mut root = Node::root();
+  root.set_content("#!/bin/idml\n");
+  root.push_child(Node::new('.', "A").unwrap()).unwrap();
+  root.set_name("document").unwrap();
+  assert_eq!("#!/bin/idml\n.A\n", root.to_source());
+  assert_eq!("#!/bin/idml\n", root.preamble());
+}
+
+#[test]
+fn _0008() {
+  // Set content ends with the line ending of the replaced content, empty content is not padded.
+  let input = ".A a\r\n.B\r\n";
+  let mut document = parse(input).unwrap();
+  document.first_with_name_mut("A").unwrap().set_content("");
+  assert_eq!(".A\r\n.B\r\n", document.to_source());
+  document.first_with_name_mut("B").unwrap().set_content("b");
+  assert_eq!(".A\r\n.B b\r\n", document.to_source());
+  assert_eq!(
+    vec!["A", "B"],
+    parse(&document.to_source()).unwrap().children().map(|node| node.name()).collect::<Vec<&str>>()
+  );
+}
+
+#[test]
+fn _0009() {
+  // Nodes added after content without line ending start on a new line.
+  let mut document = parse(".A a").unwrap();
+  document.first_with_name_mut("A").unwrap().push_child(Node::new('.', "B").unwrap()).unwrap();
+  assert_eq!(".A a\n  .B\n", document.to_source());
+  assert_eq!(vec![("A", 1), ("B", 2)], levels(&parse(&document.to_source()).unwrap()));
+  let mut document = parse(".A a\r\n  .B b").unwrap();
+  document.push_child(Node::new('.', "C").unwrap()).unwrap();
+  assert_eq!(".A a\r\n  .B b\r\n.C\n", document.to_source());
+  assert_eq!(vec![("A", 1), ("B", 2), ("C", 1)], levels(&parse(&document.to_source()).unwrap()));
+}
+
+#[test]
+fn _0010() {
+  // Inserted subtrees without final line ending are separated from the following nodes.
+  let mut document = parse(".A\n").unwrap();
+  let x = parse(".X x\n  .Y").unwrap().into_root().remove_child(0);
+  document.insert_child(0, x).unwrap();
+  assert_eq!(".X x\n  .Y\n.A\n", document.to_source());
+  assert_eq!(vec![("X", 1), ("Y", 2), ("A", 1)], levels(&parse(&document.to_source()).unwrap()));
+}
+
+#[test]
+fn _0011() {
+  // Root content without line ending is ended with the line ending of the document.
+  let mut document = parse(".A a\r\n").unwrap();
+  document.set_content("#!/bin/idml");
+  assert_eq!("#!/bin/idml\r\n.A a\r\n", document.to_source());
+  let mut root = Node::root();
+  root.set_content("\u{FEFF}");
+  root.push_child(Node::new('.', "A").unwrap()).unwrap();
+  assert_eq!("\u{FEFF}.A\n", root.to_source());
+}
+
+#[test]
+fn _0012() {
+  // Names that would be parsed back differently are rejected.
+  let err = Node::new('.', "a b").unwrap_err();
+  assert_eq!((&ErrorKind::UnexpectedCharacter(WS), 1, 3), (err.kind(), err.row(), err.column()));
+  let err = Node::new('.', ".x").unwrap_err();
+  assert_eq!((&ErrorKind::UnexpectedCharacter('.'), 1, 2), (err.kind(), err.row(), err.column()));
+  assert_eq!(&ErrorKind::UnexpectedDelimiter(TAB), Node::new(TAB, "x").unwrap_err().kind());
+  let mut document = parse(".A\n.B\n").unwrap();
+  let b = document.first_with_name_mut("B").unwrap();
+  assert_eq!(&ErrorKind::UnexpectedCharacter('.'), b.set_name(".x").unwrap_err().kind());
+  assert_eq!(&ErrorKind::UnexpectedCharacter('\n'), b.set_name("x\ny").unwrap_err().kind());
+  assert_eq!(".A\n.B\n", document.to_source());
+}
+
+#[test]
+fn _0013() {
+  // Inserted nodes must use the delimiter of the document.
+  let mut document = parse(".A\n").unwrap();
+  let err = document.push_child(Node::new('-', "B").unwrap()).unwrap_err();
+  assert_eq!(&ErrorKind::UnexpectedDelimiter('-'), err.kind());
+  let err = document.first_with_name_mut("A").unwrap().push_child(Node::new('-', "B").unwrap()).unwrap_err();
+  assert_eq!(&ErrorKind::UnexpectedDelimiter('-'), err.kind());
+  assert_eq!(".A\n", document.to_source());
+  let mut root = Node::root();
+  root.push_child(Node::new('-', "A").unwrap()).unwrap();
+  assert!(root.push_child(Node::new('.', "B").unwrap()).is_err());
+}
+
+#[test]
+fn _0014() {
+  // Inserted nodes take the comment marker of the document.
+  let options = ParseOptions::new().with_comment_marker("#").with_escaping(true);
+  let mut document = parse_with_options(".A\n", &options).unwrap();
+  let a = document.first_with_name_mut("A").unwrap();
+  assert_eq!(&ErrorKind::UnexpectedCharacter('#'), a.set_name("#x").unwrap_err().kind());
+  assert!(a.push_child(Node::new('.', "#y").unwrap()).is_err());
+  let mut b = Node::new('.', "B").unwrap();
+  b.set_content("\n#b\n");
+  a.push_child(b).unwrap();
+  assert_eq!(".A\n  .B\n.#b\n", document.to_source());
+  let source = document.to_source();
+  let reparsed = parse_with_options(&source, &options).unwrap();
+  assert_eq!("#b", reparsed.first_with_name("A").unwrap().first_with_name("B").unwrap().text());
+}
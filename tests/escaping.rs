@@ -0,0 +1,96 @@
+use idml::{parse, parse_reader_with_options, parse_with_options, ErrorKind, ParseOptions, Token, Tokenizer, WS};
+
+fn options() -> ParseOptions {
+  ParseOptions::new().with_escaping(true)
+}
+
+#[test]
+fn _0001() {
+  // Doubled delimiter at the beginning of the line escapes the delimiter in the content.
+  let input = ".script\n  ..bashrc\n  ./run.sh\n";
+  let root = parse_with_options(input, &options()).unwrap();
+  let script = root.first_with_name("script").unwrap();
+  assert_eq!("\n  .bashrc\n", script.content());
+  assert_eq!(1, script.child_count());
+  assert_eq!("/run.sh", script.children().next().unwrap().name());
+}
+
+#[test]
+fn _0002() {
+  // Escaped lines without indentation and with other delimiters.
+  let input = "-list\n-- first\n--- second\n    -- third\n-end\n";
+  let root = parse_with_options(input, &options()).unwrap();
+  assert_eq!(2, root.child_count());
+  assert_eq!("\n- first\n-- second\n    - third\n", root.first_with_name("list").unwrap().content());
+}
+
+#[test]
+fn _0003() {
+  // Escaped content is reported in tokens, the span covers the original text.
+  let input = ".A\n..B\n";
+  let tokens = Tokenizer::with_options(input, &options()).tokenize().unwrap();
+  assert_eq!(3, tokens.len());
+  assert_eq!(
+    "\n.B\n",
+    match &tokens[2] {
+      Token::NodeContent(content, _) => content.as_ref(),
+      _ => "",
+    }
+  );
+  assert_eq!(2, tokens[2].span().start().offset());
+  assert_eq!(7, tokens[2].span().end().offset());
+}
+
+#[test]
+fn _0004() {
+  // Escaped content is written back unchanged.
+  let inputs = [
+    ".script\n  ..bashrc\n  ./run.sh\n",
+    "-list\n-- first\n--- second\n    -- third\n-end\n",
+    ".A ..not escaped\n  ..escaped\r\n\t..escaped\r..escaped\r",
+  ];
+  for input in inputs {
+    let root = parse_with_options(input, &options()).unwrap();
+    assert_eq!(input, root.to_source());
+  }
+  let input = ".script\n    ..bashrc\n    .run\n";
+  assert_eq!(input, parse_with_options(input, &options()).unwrap().document(4, WS));
+}
+
+#[test]
+fn _0005() {
+  // Escaped content lines do not begin a new node in a stream.
+  let input = ".script\n  ..bashrc\n  .run\n";
+  let root = parse_reader_with_options(input.as_bytes(), &options()).unwrap();
+  let script = root.first_with_name("script").unwrap();
+  assert_eq!("\n  .bashrc\n", script.content());
+  assert_eq!(input, root.to_source());
+}
+
+#[test]
+fn _0006() {
+  // Line consisting of delimiters only.
+  let input = ".A\n..\n  ...\n";
+  let root = parse_with_options(input, &options()).unwrap();
+  assert_eq!(1, root.child_count());
+  assert_eq!("\n.\n  ..\n", root.first_with_name("A").unwrap().content());
+  assert_eq!(input, root.to_source());
+}
+
+#[test]
+fn _0007() {
+  // Escaping is disabled by default, lines beginning with the doubled delimiter begin nodes.
+  let input = "..A\n..B\n";
+  let root = parse(input).unwrap();
+  assert_eq!(vec![".A", ".B"], root.children().map(|node| node.name()).collect::<Vec<&str>>());
+  assert_eq!(input, root.to_source());
+}
+
+#[test]
+fn _0008() {
+  // With escaping enabled, the name on the first line does not begin with the delimiter nor the comment marker.
+  let err = parse_with_options("..A\n", &options()).unwrap_err();
+  assert_eq!((&ErrorKind::UnexpectedCharacter('.'), 1, 2), (err.kind(), err.row(), err.column()));
+  let err = parse_with_options(".#A\n", &options().with_comment_marker("#")).unwrap_err();
+  assert_eq!((&ErrorKind::UnexpectedCharacter('#'), 1, 2), (err.kind(), err.row(), err.column()));
+}
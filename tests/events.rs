@@ -0,0 +1,70 @@
+use idml::{events, Event, EventParser, StreamTokenizer};
+use std::io::BufReader;
+
+fn describe(event: &Event) -> String {
+  match event {
+    Event::StartNode { level, delimiter, name, .. } => format!("start {level} {delimiter}{name}"),
+    Event::Content(content, _) => format!("content {:?}", content),
+    Event::EndNode => "end".to_string(),
+    Event::Comment(comment, _) => format!("comment {:?}", comment),
+    Event::Preamble(preamble, _) => format!("preamble {:?}", preamble),
+  }
+}
+
+#[test]
+fn _0001() {
+  let input = ".A a\n  .B\n    .C c\n  .D\n.E\n";
+  let actual = events(input).map(|event| describe(&event.unwrap())).collect::<Vec<String>>();
+  let expected = vec![
+    "start 1 .A",
+    r#"content " a\n""#,
+    "start 2 .B",
+    r#"content "\n""#,
+    "start 3 .C",
+    r#"content " c\n""#,
+    "end",
+    "end",
+    "start 2 .D",
+    r#"content "\n""#,
+    "end",
+    "end",
+    "start 1 .E",
+    r#"content "\n""#,
+    "end",
+  ];
+  assert_eq!(expected, actual);
+}
+
+#[test]
+fn _0002() {
+  // Errors are reported as events.
+  let input = ".A\n  .B\n   .C\n";
+  let mut events = events(input);
+  assert!(matches!(events.next(), Some(Ok(Event::StartNode { .. }))));
+  assert!(matches!(events.next(), Some(Ok(Event::Content(..)))));
+  assert!(matches!(events.next(), Some(Ok(Event::StartNode { .. }))));
+  assert!(matches!(events.next(), Some(Ok(Event::Content(..)))));
+  assert_eq!(
+    "malformed indentation 3, expected multiplication of 2 at row 3 and column 1",
+    events.next().unwrap().unwrap_err().to_string()
+  );
+  assert!(events.next().is_none());
+  assert_eq!("empty input", idml::events("").next().unwrap().unwrap_err().to_string());
+}
+
+#[test]
+fn _0003() {
+  // Extracting values from a stream, without building the node tree.
+  let input = ".A\n  .port 80\n.B\n  .port 81\n  .host localhost\n";
+  let tokens = StreamTokenizer::new(BufReader::new(input.as_bytes()));
+  let mut ports = vec![];
+  let mut in_port = false;
+  for event in EventParser::new(tokens) {
+    match event.unwrap() {
+      Event::StartNode { name, .. } => in_port = name == "port",
+      Event::Content(content, _) if in_port => ports.push(content.trim().to_string()),
+      _ => {}
+    }
+  }
+  assert_eq!(vec!["80", "81"], ports);
+}
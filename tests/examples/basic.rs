@@ -26,3 +26,12 @@ fn _0001() {
       .text()
   );
 }
+
+#[test]
+fn _0002() {
+  let root = parse(EXAMPLE_BASIC).unwrap();
+  assert_eq!("Brilliant!", root.select("tutorial/idML/type").unwrap().next().unwrap().text());
+  let born = root.select("tutorial/*/born").map(|nodes| nodes.map(|node| node.text()).collect::<Vec<&str>>()).unwrap();
+  assert_eq!(vec!["2025", "2001", "2001", "1998"], born);
+  assert_eq!("devops", root.select("domains/[3]").unwrap().next().unwrap().text());
+}
@@ -1,4 +1,13 @@
-use idml::{parse, Parser, Token, WS};
+#[path = "common/mod.rs"]
+mod common;
+
+use common::span;
+use idml::{parse, parse_with_options, ErrorKind, ParseOptions, Parser, Position, Token};
+
+/// Options requiring the final newline.
+fn strict() -> ParseOptions {
+  ParseOptions::new().with_final_newline_required(true)
+}
 
 #[test]
 fn _0001() {
@@ -9,15 +18,21 @@ fn _0001() {
 
 #[test]
 fn _0002() {
-  // Input beginning with space(s) is invalid.
+  // Input containing only space(s) is empty.
   let input = " ";
+  assert_eq!("empty input", parse(input).unwrap_err().to_string());
+  // Input beginning with space(s) is invalid.
+  let input = " A";
   assert_eq!("unexpected character: ' ' 0x20 at row 1 and column 1", parse(input).unwrap_err().to_string());
 }
 
 #[test]
 fn _0003() {
-  // Input beginning with tab(s) is invalid.
+  // Input containing only tab(s) is empty.
   let input = "\t";
+  assert_eq!("empty input", parse(input).unwrap_err().to_string());
+  // Input beginning with tab(s) is invalid.
+  let input = "\tA";
   assert_eq!("unexpected character: '\t' 0x09 at row 1 and column 1", parse(input).unwrap_err().to_string());
 }
 
@@ -25,25 +40,36 @@ fn _0003() {
 fn _0004() {
   // Only delimiter present.
   let input = ".";
-  assert_eq!("unexpected end of input", parse(input).unwrap_err().to_string());
+  assert_eq!(
+    "unexpected end of input at row 1 and column 2",
+    parse_with_options(input, &strict()).unwrap_err().to_string()
+  );
 }
 
 #[test]
 fn _0005() {
   // No newline after empty name.
   let input = ". ";
-  assert_eq!("unexpected end of input", parse(input).unwrap_err().to_string());
+  assert_eq!(
+    "unexpected end of input at row 1 and column 3",
+    parse_with_options(input, &strict()).unwrap_err().to_string()
+  );
 }
 
 #[test]
 fn _0006() {
   for ch in '\u{0001}'..='\u{0020}' {
     println!("{}", ch as u8);
+    if matches!(ch, '\n' | '\r' | '\t' | ' ') {
+      // Blank lines before the first node are skipped.
+      assert_eq!("empty input", parse(&format!("{}", ch)).unwrap_err().to_string());
+      continue;
+    }
     let input = format!("{}", ch);
     let expected = format!("unexpected character: '{}' 0x{:02X} at row 1 and column 1", ch, ch as u8);
     assert_eq!(expected, parse(&input).unwrap_err().to_string());
   }
-  assert_eq!("unexpected character: '\r' 0x0D at row 1 and column 1", parse("\r\n").unwrap_err().to_string());
+  assert_eq!("empty input", parse("\r\n").unwrap_err().to_string());
 }
 
 #[test]
@@ -61,14 +87,20 @@ fn _0007() {
 fn _0008() {
   // Node name is not followed by a whitespace, newline or both.
   let input = ".A";
-  assert_eq!("unexpected end of input", parse(input).unwrap_err().to_string());
+  assert_eq!(
+    "unexpected end of input at row 1 and column 3",
+    parse_with_options(input, &strict()).unwrap_err().to_string()
+  );
 }
 
 #[test]
 fn _0009() {
   // Node name is not followed by a whitespace and newline.
   let input = ".A ";
-  assert_eq!("unexpected end of input", parse(input).unwrap_err().to_string());
+  assert_eq!(
+    "unexpected end of input at row 1 and column 4",
+    parse_with_options(input, &strict()).unwrap_err().to_string()
+  );
 }
 
 #[test]
@@ -91,7 +123,10 @@ fn _0012() {
   let input = r#".MODEL
     .NAMESPACE https://decision-toolkit.org/2_0001/
   "#;
-  assert_eq!("unexpected end of input", parse(input).unwrap_err().to_string());
+  assert_eq!(
+    "unexpected end of input at row 3 and column 3",
+    parse_with_options(input, &strict()).unwrap_err().to_string()
+  );
 }
 
 #[test]
@@ -102,7 +137,10 @@ fn _0013() {
     .C
    .D
 "#;
-  assert_eq!("malformed indentation 3, expected multiplication of 4", parse(input).unwrap_err().to_string());
+  assert_eq!(
+    "malformed indentation 3, expected multiplication of 4 at row 4 and column 1",
+    parse(input).unwrap_err().to_string()
+  );
 }
 
 #[test]
@@ -113,40 +151,98 @@ fn _0014() {
     .C
     .D
 "#;
-  assert_eq!("malformed indentation 4, expected multiplication of 8", parse(input).unwrap_err().to_string());
+  assert_eq!("indentation jumps from level 1 to 3 at row 2 and column 1", parse(input).unwrap_err().to_string());
 }
 
 #[test]
 fn _0015() {
   // Inconsistent indentation
   let input = ".A\n  .B\n\t\t.C\n  .D\n";
-  assert_eq!("inconsistent indentation, mixed spaces and tabs", parse(input).unwrap_err().to_string());
+  assert_eq!(
+    "inconsistent indentation, mixed spaces and tabs at row 3 and column 1",
+    parse(input).unwrap_err().to_string()
+  );
 }
 
 #[test]
 fn _0016() {
   // Inconsistent indentation
   let input = ".A\n  .B\n \t.C\n  .D\n";
-  assert_eq!("inconsistent indentation, mixed spaces and tabs", parse(input).unwrap_err().to_string());
+  assert_eq!(
+    "inconsistent indentation, mixed spaces and tabs at row 3 and column 1",
+    parse(input).unwrap_err().to_string()
+  );
 }
 
 #[test]
 fn _0017() {
   // No indentation token.
-  let tokens = vec![Token::NodeName("A".to_string(), '.')];
-  assert_eq!("expected indentation token", Parser::new(tokens).parse().unwrap_err().to_string())
+  let tokens = vec![Token::NodeName("A".into(), '.', span(1, 1, 0, 1, 3, 2))];
+  assert_eq!("expected indentation token at row 1 and column 1", Parser::new(tokens).parse().unwrap_err().to_string())
 }
 
 #[test]
 fn _0018() {
   // No node name token.
-  let tokens = vec![Token::Indentation(0, WS), Token::NodeContent("content".to_string())];
-  assert_eq!("expected node name token", Parser::new(tokens).parse().unwrap_err().to_string())
+  let tokens = vec![
+    Token::Indentation("".into(), span(1, 1, 0, 1, 1, 0)),
+    Token::NodeContent("content".into(), span(1, 2, 1, 1, 9, 8)),
+  ];
+  assert_eq!("expected node name token at row 1 and column 2", Parser::new(tokens).parse().unwrap_err().to_string())
 }
 
 #[test]
 fn _0019() {
   // No node content token.
-  let tokens = vec![Token::Indentation(0, WS), Token::NodeName("name".to_string(), '.'), Token::Indentation(0, WS)];
-  assert_eq!("expected node content token", Parser::new(tokens).parse().unwrap_err().to_string())
+  let tokens = vec![
+    Token::Indentation("".into(), span(1, 1, 0, 1, 1, 0)),
+    Token::NodeName("name".into(), '.', span(1, 1, 0, 1, 6, 5)),
+    Token::Indentation("".into(), span(2, 1, 6, 2, 1, 6)),
+  ];
+  assert_eq!("expected node content token at row 2 and column 1", Parser::new(tokens).parse().unwrap_err().to_string())
+}
+
+#[test]
+fn _0020() {
+  // Error kind and position of the malformed indentation.
+  let input = ".A\n    .B\n      .C\n";
+  let err = parse(input).unwrap_err();
+  let kind = ErrorKind::MalformedIndentation {
+    indent: 6,
+    multiplier: 4,
+    multiplier_span: span(2, 1, 3, 2, 5, 7),
+  };
+  assert_eq!(&kind, err.kind());
+  assert_eq!(Position::new(3, 1, 10), err.position());
+  assert_eq!(3, err.row());
+  assert_eq!(1, err.column());
+  assert_eq!(10, err.offset());
+}
+
+#[test]
+fn _0021() {
+  // Error kind and position of the unexpected character.
+  let input = ".A\n  .B\u{1}\n";
+  let err = parse(input).unwrap_err();
+  assert_eq!(&ErrorKind::UnexpectedCharacter('\u{1}'), err.kind());
+  assert_eq!(Position::new(2, 5, 7), err.position());
+}
+
+#[test]
+fn _0022() {
+  // Error kind and position of the unexpected end, with multibyte characters.
+  let input = ".Ä\r\n  .Ö";
+  let err = parse_with_options(input, &strict()).unwrap_err();
+  assert_eq!(&ErrorKind::UnexpectedEnd, err.kind());
+  assert_eq!(Position::new(2, 5, input.len()), err.position());
+}
+
+#[test]
+fn _0023() {
+  // Error can be propagated as a standard error.
+  fn parse_boxed(input: &str) -> Result<usize, Box<dyn std::error::Error>> {
+    Ok(parse(input)?.child_count())
+  }
+  assert_eq!(1, parse_boxed(".A\n").unwrap());
+  assert_eq!("empty input", parse_boxed("").unwrap_err().to_string());
 }
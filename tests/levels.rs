@@ -0,0 +1,76 @@
+#[path = "common/mod.rs"]
+mod common;
+
+use common::levels;
+use idml::{parse, parse_recovering, parse_with_options, Diagnostic, ErrorKind, LevelCheck, ParseOptions, Style};
+
+#[test]
+fn _0001() {
+  // Node indented more than one level deeper than the preceding node.
+  let input = ".A\n  .B\n        .C\n";
+  let err = parse(input).unwrap_err();
+  assert_eq!(&ErrorKind::LevelJump { from: 2, to: 5 }, err.kind());
+  assert_eq!("indentation jumps from level 2 to 5 at row 3 and column 1", err.to_string());
+}
+
+#[test]
+fn _0002() {
+  // First indented line is indented by two levels.
+  let input = ".A\n    .B\n  .C\n";
+  assert_eq!("indentation jumps from level 1 to 3 at row 2 and column 1", parse(input).unwrap_err().to_string());
+}
+
+#[test]
+fn _0003() {
+  // Lenient check places too deep nodes one level below the preceding node.
+  let input = ".A\n  .B\n        .C\n      .D\n  .E\n";
+  let root = parse_with_options(input, &ParseOptions::new().with_level_check(LevelCheck::Lenient)).unwrap();
+  assert_eq!(vec![("A", 1), ("B", 2), ("C", 3), ("D", 4), ("E", 2)], levels(&root));
+  assert_eq!(input, root.to_source());
+}
+
+#[test]
+fn _0004() {
+  // Lenient check rounds malformed indentation to the nearest level.
+  let input = ".A\n    .B\n     .C\n       .D\n";
+  let root = parse_with_options(input, &ParseOptions::new().with_level_check(LevelCheck::Lenient)).unwrap();
+  assert_eq!(vec![("A", 1), ("B", 2), ("C", 2), ("D", 3)], levels(&root));
+}
+
+#[test]
+fn _0005() {
+  // Level jumps are reported and recovered.
+  let (root, errors) = parse_recovering(".A\n  .B\n        .C\n  .D\n");
+  assert_eq!(
+    vec![ErrorKind::LevelJump { from: 2, to: 5 }],
+    errors.iter().map(|err| err.kind().clone()).collect::<Vec<ErrorKind>>()
+  );
+  assert_eq!(vec![("A", 1), ("B", 2), ("C", 3), ("D", 2)], levels(&root));
+}
+
+#[test]
+fn _0006() {
+  // Diagnostic for a level jump.
+  let input = ".A\n  .B\n        .C\n";
+  let err = parse(input).unwrap_err();
+  let expected = r#"error: indentation jumps from level 2 to 5
+ --> a.idml:3:1
+  |
+3 |         .C
+  | ^
+  |
+  = hint: the node must be indented at most one level deeper than level 2
+"#;
+  assert_eq!(expected, Diagnostic::from(&err).render("a.idml", input, Style::Plain));
+}
+
+#[test]
+fn _0007() {
+  // Indentation with other character does not blame the first indented line.
+  let err = parse(".A\n    .B\n\t.C\n").unwrap_err();
+  assert_eq!((&ErrorKind::InconsistentIndentation, 3, 1), (err.kind(), err.row(), err.column()));
+  let (_, errors) = parse_recovering(".A\n    .B\n\t.C\n");
+  assert!(errors.iter().all(|err| err.row() == 3));
+  let err = parse(".A\n    .B\n  .C\n").unwrap_err();
+  assert_eq!((&ErrorKind::LevelJump { from: 1, to: 3 }, 2, 1), (err.kind(), err.row(), err.column()));
+}
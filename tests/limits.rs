@@ -0,0 +1,150 @@
+use idml::{parse, parse_reader_with_options, parse_recovering_with_options, parse_with_options, ErrorKind, ParseOptions, Parser, Span, Token, WS};
+use std::io::{repeat, Read};
+
+#[test]
+fn _0001() {
+  // Nesting depth limit.
+  let options = ParseOptions::new().with_max_depth(2);
+  assert!(parse_with_options(".A\n  .B\n.C\n", &options).is_ok());
+  let err = parse_with_options(".A\n  .B\n    .C\n", &options).unwrap_err();
+  assert_eq!(&ErrorKind::DepthLimitExceeded(2), err.kind());
+  assert_eq!("nesting depth exceeds the limit of 2 at row 3 and column 5", err.to_string());
+}
+
+#[test]
+fn _0002() {
+  // Number of nodes limit.
+  let options = ParseOptions::new().with_max_nodes(2);
+  assert!(parse_with_options(".A\n  .B\n", &options).is_ok());
+  let err = parse_with_options(".A\n  .B\n.C\n", &options).unwrap_err();
+  assert_eq!("number of nodes exceeds the limit of 2 at row 3 and column 1", err.to_string());
+}
+
+#[test]
+fn _0003() {
+  // Node name length limit.
+  let options = ParseOptions::new().with_max_name_len(3);
+  assert!(parse_with_options(".abc x\n", &options).is_ok());
+  let err = parse_with_options(".abc x\n.abcd\n", &options).unwrap_err();
+  assert_eq!("node name exceeds the limit of 3 bytes at row 2 and column 5", err.to_string());
+}
+
+#[test]
+fn _0004() {
+  // Node content length limit covers all content lines.
+  let options = ParseOptions::new().with_max_content_len(6);
+  assert!(parse_with_options(".A abc\n  .B\n", &options).is_ok());
+  let err = parse_with_options(".A abc\n  def\n", &options).unwrap_err();
+  assert_eq!(&ErrorKind::ContentTooLong(6), err.kind());
+  assert_eq!("node content exceeds the limit of 6 bytes at row 2 and column 2", err.to_string());
+}
+
+#[test]
+fn _0005() {
+  // Input length limit.
+  let options = ParseOptions::new().with_max_input_len(8);
+  assert!(parse_with_options(".A\n  .B\n", &options).is_ok());
+  let err = parse_with_options(".A\n  .B\n.C\n", &options).unwrap_err();
+  assert_eq!("input exceeds the limit of 8 bytes at row 3 and column 1", err.to_string());
+}
+
+#[test]
+fn _0006() {
+  // Limits applied when reading from a stream.
+  let options = ParseOptions::new().with_max_input_len(8);
+  let err = parse_reader_with_options(".A\n  .B\n.C\n".as_bytes(), &options).unwrap_err();
+  assert_eq!("input exceeds the limit of 8 bytes at row 3 and column 1", err.to_string());
+  let options = ParseOptions::new().with_max_depth(1);
+  let err = parse_reader_with_options(".A\n  .B\n".as_bytes(), &options).unwrap_err();
+  assert_eq!(&ErrorKind::DepthLimitExceeded(1), err.kind());
+  let options = ParseOptions::new().with_max_content_len(4);
+  let err = parse_reader_with_options(".A\n.B\n  b\n  b\n".as_bytes(), &options).unwrap_err();
+  assert_eq!("node content exceeds the limit of 4 bytes at row 3 and column 4", err.to_string());
+}
+
+#[test]
+fn _0007() {
+  // Exceeding a limit stops parsing when recovering.
+  let options = ParseOptions::new().with_max_nodes(2).with_max_name_len(2);
+  let (root, errors) = parse_recovering_with_options(".A\n.B\u{1}\n.C\n.DDD\n", &options);
+  let kinds = errors.iter().map(|err| err.kind().clone()).collect::<Vec<ErrorKind>>();
+  assert_eq!(
+    vec![ErrorKind::UnexpectedCharacter('\u{1}'), ErrorKind::NodeLimitExceeded(2), ErrorKind::NameTooLong(2)],
+    kinds
+  );
+  assert_eq!(vec!["A", "B"], root.children().map(|node| node.name()).collect::<Vec<&str>>());
+}
+
+#[test]
+fn _0008() {
+  // Deep trees are cloned, serialized, formatted and dropped without overflowing the stack.
+  let depth = 100_000;
+  let span = Span::default();
+  let spaces = " ".repeat(depth);
+  let mut tokens = vec![];
+  for indent in 0..depth {
+    tokens.push(Token::Indentation(spaces[..indent].into(), span));
+    tokens.push(Token::NodeName("A".into(), '.', span));
+    tokens.push(Token::NodeContent("\n".into(), span));
+  }
+  let root = Parser::new(tokens).parse().unwrap();
+  let document = root.clone().into_owned().document(0, WS);
+  assert_eq!(".A\n".repeat(depth), document);
+  assert_eq!(depth + 1, format!("{root:?}").matches("Node {").count());
+  let mut node = &root;
+  while let Some(child) = node.children().next() {
+    node = child;
+  }
+  assert_eq!(depth, node.level());
+}
+
+#[test]
+fn _0009() {
+  // No limits are set by default.
+  let input = format!(".{} {}\n", "A".repeat(10_000), "a".repeat(100_000));
+  assert!(parse(&input).is_ok());
+}
+
+#[test]
+fn _0010() {
+  // Name and content limits stop reading endless nodes from a stream.
+  let options = ParseOptions::new().with_max_content_len(10);
+  let err = parse_reader_with_options(".A ".as_bytes().chain(repeat(b'a')), &options).unwrap_err();
+  assert_eq!("node content exceeds the limit of 10 bytes at row 1 and column 13", err.to_string());
+  let err = parse_reader_with_options(".A\n.B\n".as_bytes().chain(repeat(b'\n')), &options).unwrap_err();
+  assert_eq!("node content exceeds the limit of 10 bytes at row 12 and column 1", err.to_string());
+  let options = ParseOptions::new().with_max_name_len(10);
+  let err = parse_reader_with_options(".A\n.".as_bytes().chain(repeat(b'a')), &options).unwrap_err();
+  assert_eq!("node name exceeds the limit of 10 bytes at row 2 and column 12", err.to_string());
+}
+
+#[test]
+fn _0011() {
+  // Limits are reported at the same positions when reading from a stream.
+  let options = ParseOptions::new().with_max_name_len(4).with_max_content_len(8).with_comment_marker("#");
+  let inputs = [
+    ".A abcdefghij\n",
+    ".A\r\n  ab\r\n  cd\r\n  ef\r\n",
+    ".A\r  ab\r  cd\r  ef\r",
+    ".A ää\n  ääää\n",
+    "\u{FEFF}.A\n.abcdé\n",
+    ".A ab\n# comment\n\n  cdefgh\n  ijk\n",
+    ".A ab\n# comment\n\n  cd\n# comment\n  efgh\n.B\n",
+    ".A\n..abcdefgh\n",
+    ".A\n    \n      \n.B\n",
+  ];
+  let long = "x".repeat(20_000);
+  let long_inputs = [
+    format!(".A ab\n  c{long}\n"),
+    format!(".A ab\n# {long}\n  cd\n"),
+    format!(".A ab\n{}.B\n", " ".repeat(20_000)),
+    format!(".A\n.{long}\n"),
+  ];
+  for input in inputs.iter().copied().chain(long_inputs.iter().map(String::as_str)) {
+    assert_eq!(
+      parse_with_options(input, &options).map(|document| document.to_source()),
+      parse_reader_with_options(input.as_bytes(), &options).map(|document| document.to_source()),
+      "{input:?}"
+    );
+  }
+}
@@ -1,4 +1,30 @@
+// Test files include shared helpers themselves, so they can also be compiled as separate test targets.
+#![allow(clippy::duplicate_mod)]
+
+mod borrowing;
+mod comments;
+mod detection;
+mod diagnostics;
+mod document;
+mod editing;
+mod escaping;
+mod events;
 mod examples;
 mod indexes;
 mod invalid_input;
+mod levels;
+mod limits;
+mod options;
+mod paths;
+mod preamble;
+mod predicates;
+mod recovering;
+mod source;
+mod spans;
+mod streaming;
+mod tab_stops;
+mod tokens;
+mod traversal;
+mod tree;
 mod valid_input;
+mod visitor;
@@ -0,0 +1,116 @@
+use idml::{parse, parse_reader_with_options, parse_recovering_with_options, parse_with_options, Diagnostic, ErrorKind, LevelCheck, ParseOptions, Position, Style, TAB, WS};
+
+#[test]
+fn _0001() {
+  // Default options.
+  let options = ParseOptions::default();
+  assert_eq!(None, options.delimiters());
+  assert_eq!(None, options.indent());
+  assert!(!options.final_newline_required());
+  assert_eq!(LevelCheck::Strict, options.level_check());
+  assert_eq!(ParseOptions::new(), options);
+}
+
+#[test]
+fn _0002() {
+  // Document beginning with a letter is rejected when the delimiter is specified.
+  let options = ParseOptions::new().with_delimiter('.');
+  assert_eq!(Some(&['.'][..]), options.delimiters());
+  assert!(parse("ab\n").is_ok());
+  let err = parse_with_options("ab\n", &options).unwrap_err();
+  assert_eq!(&ErrorKind::UnexpectedDelimiter('a'), err.kind());
+  assert_eq!("unexpected delimiter: 'a' 0x61 at row 1 and column 1", err.to_string());
+  assert!(parse_with_options(".a\n", &options).is_ok());
+}
+
+#[test]
+fn _0003() {
+  // Any of allowed delimiters is accepted.
+  let options = ParseOptions::new().with_delimiters(['.', '-']);
+  assert_eq!("A", parse_with_options(".A\n", &options).unwrap().first_with_name("A").unwrap().name());
+  assert_eq!("A", parse_with_options("-A\n", &options).unwrap().first_with_name("A").unwrap().name());
+  assert_eq!(
+    "unexpected delimiter: '*' 0x2A at row 1 and column 1",
+    parse_with_options("*A\n", &options).unwrap_err().to_string()
+  );
+}
+
+#[test]
+fn _0004() {
+  // Fixed indentation unit is used instead of the first indented line.
+  let options = ParseOptions::new().with_indent(2, WS);
+  let root = parse_with_options(".A\n  .B\n    .C\n", &options).unwrap();
+  let b = root.first_with_name("A").unwrap().first_with_name("B").unwrap();
+  assert_eq!(2, b.level());
+  assert_eq!(3, b.first_with_name("C").unwrap().level());
+  let err = parse_with_options(".A\n    .B\n", &options).unwrap_err();
+  assert_eq!("indentation jumps from level 1 to 3 at row 2 and column 1", err.to_string());
+  let err = parse_with_options(".A\n   .B\n", &options).unwrap_err();
+  assert_eq!("malformed indentation 3, expected multiplication of 2 at row 2 and column 1", err.to_string());
+}
+
+#[test]
+fn _0005() {
+  // Fixed indentation character.
+  let options = ParseOptions::new().with_indent(1, TAB);
+  assert!(parse_with_options(".A\n\t.B\n", &options).is_ok());
+  let err = parse_with_options(".A\n .B\n", &options).unwrap_err();
+  assert_eq!(&ErrorKind::UnexpectedCharacter(WS), err.kind());
+  assert_eq!(Position::new(2, 1, 3), err.position());
+}
+
+#[test]
+fn _0006() {
+  // Missing final newline is tolerated by default.
+  let options = ParseOptions::new();
+  let root = parse_with_options(".A a\n  .B b", &options).unwrap();
+  let b = root.first_with_name("A").unwrap().first_with_name("B").unwrap();
+  assert_eq!(" b", b.content());
+  let root = parse_with_options(".A", &options).unwrap();
+  assert_eq!("", root.first_with_name("A").unwrap().content());
+  let input = ".A\n  .B\n  ";
+  let root = parse_with_options(input, &options).unwrap();
+  assert_eq!(input, root.to_source());
+  let options = ParseOptions::new().with_final_newline_required(true);
+  assert!(parse_with_options(".A a\n  .B b", &options).is_err());
+}
+
+#[test]
+fn _0007() {
+  // Lenient level check places malformed indentation at the nearest level.
+  let input = ".A\n    .B\n     .C\n   .D\n";
+  assert!(parse(input).is_err());
+  let options = ParseOptions::new().with_level_check(LevelCheck::Lenient);
+  let root = parse_with_options(input, &options).unwrap();
+  let a = root.first_with_name("A").unwrap();
+  assert_eq!(vec!["B", "C", "D"], a.children().map(|node| node.name()).collect::<Vec<&str>>());
+  assert!(a.children().all(|node| node.level() == 2));
+}
+
+#[test]
+fn _0008() {
+  // Options are applied while recovering and when reading from a stream.
+  let options = ParseOptions::new().with_delimiter('-');
+  let (_, errors) = parse_recovering_with_options(".A\n-B\n", &options);
+  assert_eq!(vec![ErrorKind::UnexpectedDelimiter('.')], errors.iter().map(|err| err.kind().clone()).collect::<Vec<_>>());
+  let err = parse_reader_with_options(".A\n".as_bytes(), &options).unwrap_err();
+  assert_eq!(&ErrorKind::UnexpectedDelimiter('.'), err.kind());
+  let options = ParseOptions::new().with_final_newline_required(false);
+  assert_eq!(".A\n  .B", parse_reader_with_options(".A\n  .B".as_bytes(), &options).unwrap().to_source());
+}
+
+#[test]
+fn _0009() {
+  // Diagnostic for malformed indentation with the unit set in options.
+  let input = ".A\n   .B\n";
+  let err = parse_with_options(input, &ParseOptions::new().with_indent(2, WS)).unwrap_err();
+  let expected = r#"error: malformed indentation 3, expected multiplication of 2
+ --> input.idml:2:1
+  |
+2 |    .B
+  | ^^^
+  |
+  = hint: indentation 3 is not a multiple of 2 set in options
+"#;
+  assert_eq!(expected, Diagnostic::from(&err).render("input.idml", input, Style::Plain));
+}
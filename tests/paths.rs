@@ -0,0 +1,98 @@
+#[path = "common/mod.rs"]
+mod common;
+
+use common::texts;
+use idml::{parse, ErrorKind, Path};
+
+const INPUT: &str = r#".company Engos Software
+.domains
+    . business analysts
+    . software developers
+    . data engineers
+.tutorial
+    .idML
+        .type Brilliant!
+        .born 2025
+    .yaml
+        .type Awesome!
+        .born 2001
+    .json:
+        .type Great!
+        .born 2001
+"#;
+
+#[test]
+fn _0001() {
+  // Path of node names.
+  let root = parse(INPUT).unwrap();
+  assert_eq!(vec!["Brilliant!"], texts(root.select("tutorial/idML/type").unwrap()));
+  assert_eq!(vec!["Great!"], texts(root.select("/tutorial/json:/type").unwrap()));
+  assert_eq!(0, root.select("tutorial/xml/type").unwrap().count());
+}
+
+#[test]
+fn _0002() {
+  // Wildcards.
+  let root = parse(INPUT).unwrap();
+  assert_eq!(vec!["Brilliant!", "Awesome!", "Great!"], texts(root.select("tutorial/*/type").unwrap()));
+  assert_eq!(vec!["2025", "2001", "2001"], texts(root.select("**/born").unwrap()));
+  assert_eq!(vec!["", "Brilliant!", "2025"], texts(root.select("tutorial/idML/**").unwrap()));
+  assert_eq!(3, root.select("**/**/type").unwrap().count());
+}
+
+#[test]
+fn _0003() {
+  // Positional indexes, counting from zero.
+  let root = parse(INPUT).unwrap();
+  assert_eq!(vec!["data engineers"], texts(root.select("domains/[2]").unwrap()));
+  assert_eq!(vec!["Awesome!"], texts(root.select("tutorial/*[1]/type").unwrap()));
+  assert_eq!(vec!["Brilliant!", "Awesome!", "Great!"], texts(root.select("tutorial/*/*[0]").unwrap()));
+  assert_eq!(0, root.select("domains/[3]").unwrap().count());
+}
+
+#[test]
+fn _0004() {
+  // Anonymous nodes and alternative names.
+  let root = parse(INPUT).unwrap();
+  assert_eq!(
+    vec!["business analysts", "software developers", "data engineers"],
+    texts(root.select(r#"domains/"""#).unwrap())
+  );
+  assert_eq!(vec!["software developers"], texts(root.select(r#"domains/""[1]"#).unwrap()));
+  assert_eq!(vec!["Brilliant!", "Great!"], texts(root.select("tutorial/idML|json:/type").unwrap()));
+}
+
+#[test]
+fn _0005() {
+  // Compiled path is reused.
+  let path: Path = "tutorial/*/born".parse().unwrap();
+  assert_eq!("tutorial/*/born", path.to_string());
+  let first = parse(INPUT).unwrap();
+  let second = parse(".tutorial\n  .rust\n    .born 2015\n").unwrap();
+  assert_eq!(vec!["2025", "2001", "2001"], texts(first.select_path(&path)));
+  assert_eq!(vec!["2015"], texts(path.select(&second)));
+}
+
+#[test]
+fn _0006() {
+  // Invalid path expressions.
+  let err = Path::new("tutorial//type").unwrap_err();
+  assert_eq!(&ErrorKind::InvalidPath("expected node name".to_string()), err.kind());
+  assert_eq!("invalid path, expected node name at row 1 and column 10", err.to_string());
+  assert_eq!("invalid path, expected ']' at row 1 and column 11", Path::new("domains/[2").unwrap_err().to_string());
+  assert_eq!(
+    "invalid path, expected quoted value at row 1 and column 15",
+    Path::new("domains/[text=a]").unwrap_err().to_string()
+  );
+  assert_eq!("invalid path, expected closing quote at row 1 and column 4", Path::new("a/\"").unwrap_err().to_string());
+  assert!(parse(INPUT).unwrap().select("").is_err());
+}
+
+#[test]
+fn _0007() {
+  // Nodes selected relative to nested nodes are returned in document order.
+  let root = parse(".X\n  .b 1\n  .Y\n    .b 2\n  .b 3\n").unwrap();
+  assert_eq!(vec!["1", "2", "3"], texts(root.select("X/**/b").unwrap()));
+  assert_eq!(vec!["1", "2", "3"], texts(root.select("**/**/b").unwrap()));
+  assert_eq!(vec!["", "1", "", "2", "3"], texts(root.select("**/*").unwrap()));
+}
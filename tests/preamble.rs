@@ -0,0 +1,107 @@
+use idml::{
+  events, parse, parse_reader, parse_reader_with_options, parse_recovering_with_options, parse_with_options, tokenize, ErrorKind, Event, ParseOptions, Position, Token, WS,
+};
+
+#[test]
+fn _0001() {
+  // Byte order mark is skipped.
+  let input = "\u{FEFF}.A a\n  .B b\n";
+  let root = parse(input).unwrap();
+  let a = root.first_with_name("A").unwrap();
+  assert_eq!('.', a.delimiter());
+  assert_eq!("b", a.first_with_name("B").unwrap().text());
+  assert_eq!("\u{FEFF}", root.content());
+  assert_eq!("", root.preamble());
+  assert_eq!(input, root.to_source());
+}
+
+#[test]
+fn _0002() {
+  // Leading blank lines are skipped.
+  let input = "\n  \r\n\t\n.A a\n";
+  let root = parse(input).unwrap();
+  assert_eq!(1, root.child_count());
+  assert_eq!(Position::new(4, 1, 7), root.first_with_name("A").unwrap().span().start());
+  assert_eq!("\n  \r\n\t\n", root.preamble());
+  assert_eq!(input, root.to_source());
+  assert_eq!(input, root.document(2, WS));
+}
+
+#[test]
+fn _0003() {
+  // Preamble before the first node.
+  let input = "#!/usr/bin/env idml\n// Licensed under MIT\n  indented\n\n.A a\n  .B b\n";
+  let options = ParseOptions::new().with_delimiter('.').with_preamble_allowed(true);
+  let root = parse_with_options(input, &options).unwrap();
+  assert_eq!("#!/usr/bin/env idml\n// Licensed under MIT\n  indented\n\n", root.preamble());
+  assert_eq!(vec!["A"], root.children().map(|node| node.name()).collect::<Vec<&str>>());
+  assert_eq!(input, root.to_source());
+  assert!(parse_with_options(input, &ParseOptions::new().with_delimiter('.')).is_err());
+}
+
+#[test]
+fn _0004() {
+  // Preamble is reported as a token.
+  let input = "\u{FEFF}\n.A\n";
+  let tokens = tokenize(input).unwrap();
+  assert_eq!(Token::Preamble("\u{FEFF}\n".into(), tokens[0].span()), tokens[0]);
+  assert_eq!(4, tokens[0].span().end().offset());
+}
+
+#[test]
+fn _0005() {
+  // Document containing only the preamble is empty.
+  let options = ParseOptions::new().with_delimiter('.').with_preamble_allowed(true);
+  let err = parse_with_options("#!/bin/sh\necho\n", &options).unwrap_err();
+  assert_eq!(&ErrorKind::EmptyInput, err.kind());
+  assert_eq!("empty input", parse("\u{FEFF}\n\n").unwrap_err().to_string());
+}
+
+#[test]
+fn _0006() {
+  // Preamble, leading blank lines and byte order mark in a stream.
+  let input = "\u{FEFF}\n.A a\n  .B b\n";
+  let root = parse_reader(input.as_bytes()).unwrap();
+  assert_eq!("\n", root.preamble());
+  assert_eq!(input, root.to_source());
+  let input = "#!/usr/bin/env idml\n\n-A a\n  -B b\n";
+  let options = ParseOptions::new().with_delimiter('-').with_preamble_allowed(true);
+  let root = parse_reader_with_options(input.as_bytes(), &options).unwrap();
+  assert_eq!("#!/usr/bin/env idml\n\n", root.preamble());
+  assert_eq!("b", root.first_with_name("A").unwrap().first_with_name("B").unwrap().text());
+  assert_eq!(input, root.to_source());
+}
+
+#[test]
+fn _0007() {
+  // Preamble followed by comments.
+  let input = "#!/bin/idml\n; comment\n.A\n";
+  let options = ParseOptions::new().with_delimiter('.').with_preamble_allowed(true).with_comment_marker(";");
+  let root = parse_with_options(input, &options).unwrap();
+  assert_eq!("#!/bin/idml\n", root.preamble());
+  assert_eq!(vec!["; comment"], root.first_with_name("A").unwrap().comments().collect::<Vec<&str>>());
+  assert_eq!(input, root.to_source());
+}
+
+#[test]
+fn _0008() {
+  // Preamble is reported as a dedicated event, before the first node is started.
+  let input = "\n\n.A a\n";
+  let events = events(input).map(|event| event.unwrap()).collect::<Vec<Event>>();
+  assert!(matches!(&events[0], Event::Preamble(preamble, span) if preamble == "\n\n" && span.end().offset() == 2));
+  assert!(matches!(&events[1], Event::StartNode { name, .. } if name == "A"));
+  assert!(matches!(&events[2], Event::Content(content, _) if content == " a\n"));
+}
+
+#[test]
+fn _0009() {
+  // Preamble requires explicitly allowed delimiters, otherwise any preamble line could begin the first node.
+  let input = "#!/bin/x\n.A\n";
+  let options = ParseOptions::new().with_preamble_allowed(true);
+  let expected = "preamble requires explicitly allowed delimiters at row 1 and column 1";
+  assert_eq!(expected, parse_with_options(input, &options).unwrap_err().to_string());
+  assert_eq!(expected, parse_reader_with_options(input.as_bytes(), &options).unwrap_err().to_string());
+  let (root, errors) = parse_recovering_with_options(input, &options);
+  assert_eq!(&ErrorKind::PreambleWithoutDelimiters, errors[0].kind());
+  assert_eq!(0, root.child_count());
+}
@@ -0,0 +1,109 @@
+#[path = "common/mod.rs"]
+mod common;
+
+use common::texts;
+use idml::{parse, ErrorKind, Node, Path};
+
+const INPUT: &str = r#".company Engos Software
+.domains
+    . business analysts
+    . software developers
+    . data engineers
+.tutorial
+    .idML
+        .type Brilliant!
+        .born 2025
+    .yaml
+        .type Awesome!
+        .born 2001
+        .name YAML
+    .json:
+        .type Great!
+        .born 2001
+        .name JSON
+"#;
+
+fn names<'a>(nodes: impl Iterator<Item = &'a Node<'a>>) -> Vec<&'a str> {
+  nodes.map(|node| node.name()).collect()
+}
+
+#[test]
+fn _0001() {
+  // Comparing the text of child nodes.
+  let root = parse(INPUT).unwrap();
+  assert_eq!(vec!["YAML", "JSON"], texts(root.select(r#"tutorial/*[born="2001"]/name"#).unwrap()));
+  assert_eq!(vec!["idML"], names(root.select(r#"tutorial/*[born != "2001"]"#).unwrap()));
+  assert_eq!(vec!["yaml"], names(root.select(r#"tutorial/*[type~="Awesome"]"#).unwrap()));
+  assert_eq!(vec!["json:"], names(root.select(r#"tutorial/*[type ends-with "eat!"]"#).unwrap()));
+  assert!(root.select(r#"tutorial/*[born="1999"]"#).unwrap().next().is_none());
+}
+
+#[test]
+fn _0002() {
+  // Comparing the text of the node itself.
+  let root = parse(INPUT).unwrap();
+  assert_eq!(vec!["data engineers"], texts(root.select(r#"domains/*[text starts-with "data"]"#).unwrap()));
+  assert_eq!(vec!["business analysts"], texts(root.select(r#"domains/*[text="business analysts"]"#).unwrap()));
+  assert_eq!(vec!["2025"], texts(root.select(r#"**/born[text~="25"]"#).unwrap()));
+}
+
+#[test]
+fn _0003() {
+  // Testing the existence of nodes.
+  let root = parse(INPUT).unwrap();
+  assert_eq!(vec!["yaml", "json:"], names(root.select("tutorial/*[name]").unwrap()));
+  assert_eq!(vec!["idML"], names(root.select("tutorial/*[not(name)]").unwrap()));
+  assert_eq!(vec!["tutorial"], names(root.select("*[*/type]").unwrap()));
+}
+
+#[test]
+fn _0004() {
+  // Combining predicates.
+  let root = parse(INPUT).unwrap();
+  assert_eq!(vec!["json:"], names(root.select(r#"tutorial/*[born="2001" and not(type~="Awesome")]"#).unwrap()));
+  assert_eq!(vec!["idML", "yaml"], names(root.select(r#"tutorial/*[born="2025" or type="Awesome!"]"#).unwrap()));
+  // Conjunction takes precedence over disjunction.
+  assert_eq!(
+    vec!["idML", "json:"],
+    names(root.select(r#"tutorial/*[type="Brilliant!" or born="2001" and name="JSON"]"#).unwrap())
+  );
+  assert_eq!(
+    vec!["idML"],
+    names(root.select(r#"tutorial/*[(type="Brilliant!" or born="2001") and born!="2001"]"#).unwrap())
+  );
+}
+
+#[test]
+fn _0005() {
+  // Predicates followed by index, names colliding with keywords are quoted.
+  let root = parse(INPUT).unwrap();
+  assert_eq!(vec!["json:"], names(root.select(r#"tutorial/*[born="2001"][1]"#).unwrap()));
+  assert_eq!(vec!["idML"], names(root.select(r#"tutorial/*[0][born]"#).unwrap()));
+  let root = parse(".a\n  .text first\n  .or second\n").unwrap();
+  assert_eq!(vec!["a"], names(root.select(r#"*["text"="first" and "or"]"#).unwrap()));
+  assert!(root.select(r#"*[text="first"]"#).unwrap().next().is_none());
+}
+
+#[test]
+fn _0006() {
+  // Invalid predicates.
+  assert_eq!("invalid path, expected ')' at row 1 and column 14", Path::new("a[not(b and c]").unwrap_err().to_string());
+  assert_eq!("invalid path, expected '(' at row 1 and column 7", Path::new("a[not b]").unwrap_err().to_string());
+  assert_eq!("invalid path, expected quoted value at row 1 and column 5", Path::new("a[b=]").unwrap_err().to_string());
+  assert_eq!("invalid path, expected node name at row 1 and column 3", Path::new("a[=\"b\"]").unwrap_err().to_string());
+  assert_eq!("invalid path, expected ']' at row 1 and column 5", Path::new("a[b c]").unwrap_err().to_string());
+}
+
+#[test]
+fn _0007() {
+  // Deeply nested predicates are rejected, long chains of predicates are accepted.
+  let nested = format!("a[{}b{}]", "(".repeat(100_000), ")".repeat(100_000));
+  assert_eq!("invalid path, nesting too deep at row 1 and column 67", Path::new(&nested).unwrap_err().to_string());
+  let nested = format!("a{}", "[not(b".repeat(100_000));
+  assert!(matches!(Path::new(&nested).unwrap_err().kind(), ErrorKind::InvalidPath(_)));
+  let nested = format!("a{}{}", "[b".repeat(64), "]".repeat(64));
+  assert!(Path::new(&nested).is_ok());
+  let chain = format!("*[{}]", vec![r#"text="x""#; 100_000].join(" or "));
+  let root = parse(".a x\n.b y\n").unwrap();
+  assert_eq!(vec!["a"], names(root.select(&chain).unwrap()));
+}
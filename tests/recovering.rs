@@ -0,0 +1,93 @@
+use idml::{parse_recovering, parse_recovering_with_options, ErrorKind, ParseOptions, Parser, Position, Span, Token};
+
+#[test]
+fn _0001() {
+  // Valid input produces no errors.
+  let (root, errors) = parse_recovering(".A\n  .B\n.C\n");
+  assert!(errors.is_empty());
+  assert_eq!(2, root.child_count());
+}
+
+#[test]
+fn _0002() {
+  // Malformed and inconsistent indentation on several lines.
+  let input = ".A\n    .B\n     .C\n    .D\n  \t.E\n\t\t\t\t.F\n";
+  let (root, errors) = parse_recovering(input);
+  let messages = errors.iter().map(|err| err.to_string()).collect::<Vec<String>>();
+  assert_eq!(
+    vec![
+      "malformed indentation 5, expected multiplication of 4 at row 3 and column 1",
+      "inconsistent indentation, mixed spaces and tabs at row 5 and column 1",
+      "malformed indentation 3, expected multiplication of 4 at row 5 and column 1",
+      "inconsistent indentation, mixed spaces and tabs at row 6 and column 1",
+    ],
+    messages
+  );
+  let a = root.first_with_name("A").unwrap();
+  let names = a.children().map(|node| node.name()).collect::<Vec<&str>>();
+  assert_eq!(vec!["B", "C", "D", "E", "F"], names);
+}
+
+#[test]
+fn _0003() {
+  // Stray characters in node names and missing final newline.
+  let input = ".A\u{1}B\n.C\u{2} c";
+  let (root, errors) = parse_recovering_with_options(input, &ParseOptions::new().with_final_newline_required(true));
+  let kinds = errors.iter().map(|err| err.kind().clone()).collect::<Vec<ErrorKind>>();
+  assert_eq!(
+    vec![ErrorKind::UnexpectedCharacter('\u{1}'), ErrorKind::UnexpectedCharacter('\u{2}'), ErrorKind::UnexpectedEnd],
+    kinds
+  );
+  let names = root.children().map(|node| node.name()).collect::<Vec<&str>>();
+  assert_eq!(vec!["AB", "C"], names);
+  assert_eq!("c", root.first_with_name("C").unwrap().text());
+}
+
+#[test]
+fn _0004() {
+  // Lines before the first node are skipped, blank lines are not reported.
+  let input = "\n  text\n.A\n";
+  let (root, errors) = parse_recovering(input);
+  assert_eq!(1, errors.len());
+  assert_eq!(Position::new(2, 1, 1), errors[0].position());
+  assert_eq!(1, root.child_count());
+  assert_eq!("A", root.children().next().unwrap().name());
+}
+
+#[test]
+fn _0005() {
+  // Empty input.
+  let (root, errors) = parse_recovering("");
+  assert_eq!(0, root.child_count());
+  assert_eq!(vec!["empty input"], errors.iter().map(|err| err.to_string()).collect::<Vec<String>>());
+}
+
+#[test]
+fn _0006() {
+  // Unexpected tokens are skipped until the next indentation token.
+  let span = Span::default();
+  let tokens = vec![
+    Token::Indentation("".into(), span),
+    Token::NodeContent("content".into(), span),
+    Token::Indentation("".into(), span),
+    Token::NodeName("A".into(), '.', span),
+    Token::NodeContent("\n".into(), span),
+  ];
+  let (root, errors) = Parser::new(tokens).parse_recovering();
+  assert_eq!(vec![ErrorKind::ExpectedNodeName], errors.iter().map(|err| err.kind().clone()).collect::<Vec<ErrorKind>>());
+  assert_eq!("A", root.children().next().unwrap().name());
+}
+
+#[test]
+fn _0007() {
+  // Different errors at the same position are all reported.
+  let (_, errors) = parse_recovering(".A\n  .B\n\t\t\t\t\t\t.C\n");
+  let messages = errors.iter().map(|err| err.to_string()).collect::<Vec<String>>();
+  assert_eq!(
+    vec![
+      "inconsistent indentation, mixed spaces and tabs at row 3 and column 1",
+      "indentation jumps from level 2 to 4 at row 3 and column 1",
+    ],
+    messages
+  );
+}
@@ -0,0 +1,62 @@
+use idml::{parse, parse_reader, NULL, TAB, WS};
+
+#[test]
+fn _0001() {
+  // Parsed document is reproduced byte by byte.
+  let inputs = [
+    ".A\n",
+    ".A a\n",
+    ".A\n  .B\n    .C\n  .D\n.E\n",
+    ".A\n\t.B\n\t\t.C\n\t.D\n",
+    ".A a\r\n  .B b\r\n\r\n  .C\r\n",
+    ".A a\r  .B b\r",
+    ".A\n\n\n    .B\n\n        .C c\n\n\n",
+    ".A   multiple   spaces   \n   .B\n",
+    "-A\n   -B  \t \n      -C\n",
+    ".A\n  first line\n    second line\n  .B\n",
+    ".Ä ö\n  .Ü ß\n",
+  ];
+  for input in inputs {
+    assert_eq!(input, parse(input).unwrap().to_source());
+  }
+}
+
+#[test]
+fn _0002() {
+  // Example document is reproduced byte by byte.
+  let input = include_str!("examples/basic.idml");
+  assert_eq!(input, parse(input).unwrap().to_source());
+  assert_eq!(input.replace('\n', "\r\n"), parse(&input.replace('\n', "\r\n")).unwrap().to_source());
+}
+
+#[test]
+fn _0003() {
+  // Original indentation is available for each node.
+  let input = ".A\n   .B\n      .C\n";
+  let root = parse(input).unwrap();
+  let a = root.first_with_name("A").unwrap();
+  let b = a.first_with_name("B").unwrap();
+  let c = b.first_with_name("C").unwrap();
+  assert_eq!((0, NULL), (a.indent(), a.indent_char()));
+  assert_eq!((3, WS), (b.indent(), b.indent_char()));
+  assert_eq!((6, WS), (c.indent(), c.indent_char()));
+  let root = parse(".A\n\t.B\n").unwrap();
+  let b = root.first_with_name("A").unwrap().first_with_name("B").unwrap();
+  assert_eq!((1, TAB), (b.indent(), b.indent_char()));
+}
+
+#[test]
+fn _0004() {
+  // Source of a single node covers the node and all its descendants.
+  let input = ".A\n  .B b\r\n\n    .C\n  .D\n";
+  let root = parse(input).unwrap();
+  let b = root.first_with_name("A").unwrap().first_with_name("B").unwrap();
+  assert_eq!("  .B b\r\n\n    .C\n", b.to_source());
+}
+
+#[test]
+fn _0005() {
+  // Documents read from a stream are reproduced byte by byte.
+  let input = ".A a\r\n  .B b\r\n\r\n  .C\n\n";
+  assert_eq!(input, parse_reader(input.as_bytes()).unwrap().to_source());
+}
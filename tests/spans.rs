@@ -0,0 +1,52 @@
+#[path = "common/mod.rs"]
+mod common;
+
+use common::span;
+use idml::parse;
+
+#[test]
+fn _0001() {
+  let input = ".A content\n";
+  let root = parse(input).unwrap();
+  let node = root.first_with_name("A").unwrap();
+  assert_eq!(span(1, 1, 0, 1, 3, 2), node.name_span());
+  assert_eq!(span(1, 3, 2, 2, 1, 11), node.content_span());
+  assert_eq!(span(1, 1, 0, 2, 1, 11), node.span());
+  assert_eq!(" content\n", &input[node.content_span().start().offset()..node.content_span().end().offset()]);
+}
+
+#[test]
+fn _0002() {
+  let input = ".A\n  .B b\n  .C\n    c\n  .D\n.E\n";
+  let root = parse(input).unwrap();
+  let a = root.first_with_name("A").unwrap();
+  assert_eq!(span(1, 1, 0, 1, 3, 2), a.name_span());
+  assert_eq!(span(1, 3, 2, 2, 1, 3), a.content_span());
+  assert_eq!(span(1, 1, 0, 6, 1, 26), a.span());
+  let b = a.first_with_name("B").unwrap();
+  assert_eq!(span(2, 3, 5, 2, 5, 7), b.name_span());
+  assert_eq!(span(2, 5, 7, 3, 1, 10), b.content_span());
+  assert_eq!(span(2, 3, 5, 3, 1, 10), b.span());
+  let c = a.first_with_name("C").unwrap();
+  assert_eq!(span(3, 3, 12, 3, 5, 14), c.name_span());
+  assert_eq!(span(3, 5, 14, 5, 1, 21), c.content_span());
+  assert_eq!("\n    c\n", &input[c.content_span().start().offset()..c.content_span().end().offset()]);
+  let e = root.first_with_name("E").unwrap();
+  assert_eq!(span(6, 1, 26, 6, 3, 28), e.name_span());
+  assert_eq!(span(6, 3, 28, 7, 1, 29), e.content_span());
+  assert_eq!(span(1, 1, 0, 7, 1, 29), root.span());
+}
+
+#[test]
+fn _0003() {
+  let input = ".Ä\r\n\t.Ö ü\r\n";
+  let root = parse(input).unwrap();
+  let a = root.first_with_name("Ä").unwrap();
+  assert_eq!(span(1, 1, 0, 1, 3, 3), a.name_span());
+  assert_eq!(span(1, 3, 3, 2, 1, 5), a.content_span());
+  let o = a.first_with_name("Ö").unwrap();
+  assert_eq!(span(2, 2, 6, 2, 4, 9), o.name_span());
+  assert_eq!(span(2, 4, 9, 3, 1, 14), o.content_span());
+  assert_eq!(" ü\r\n", &input[o.content_span().start().offset()..o.content_span().end().offset()]);
+  assert_eq!("2:2-3:1", o.span().to_string());
+}
@@ -0,0 +1,119 @@
+use idml::{parse, parse_reader, tokenize, ErrorKind, Node, Position, StreamTokenizer, Token};
+use std::io::{BufReader, Read};
+
+const EXAMPLE_BASIC: &str = include_str!("examples/basic.idml");
+
+/// Reader returning the input byte by byte.
+struct SlowReader<'a>(&'a [u8]);
+
+impl Read for SlowReader<'_> {
+  fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+    if self.0.is_empty() || buf.is_empty() {
+      return Ok(0);
+    }
+    buf[0] = self.0[0];
+    self.0 = &self.0[1..];
+    Ok(1)
+  }
+}
+
+/// Reader failing after returning the input.
+struct FailingReader<'a>(&'a [u8]);
+
+impl Read for FailingReader<'_> {
+  fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+    if self.0.is_empty() {
+      return Err(std::io::Error::other("broken pipe"));
+    }
+    let len = self.0.len().min(buf.len());
+    buf[..len].copy_from_slice(&self.0[..len]);
+    self.0 = &self.0[len..];
+    Ok(len)
+  }
+}
+
+fn assert_same_nodes(expected: &Node, actual: &Node) {
+  assert_eq!(expected.name(), actual.name());
+  assert_eq!(expected.content(), actual.content());
+  assert_eq!(expected.span(), actual.span());
+  assert_eq!(expected.name_span(), actual.name_span());
+  assert_eq!(expected.content_span(), actual.content_span());
+  assert_eq!(expected.child_count(), actual.child_count());
+  for (expected_child, actual_child) in expected.children().zip(actual.children()) {
+    assert_same_nodes(expected_child, actual_child);
+  }
+}
+
+#[test]
+fn _0001() {
+  let inputs = [
+    EXAMPLE_BASIC,
+    ".A\n",
+    ".A\r",
+    ".A\r\n  .B b\r\n\r\n  c\r\n",
+    ".A\r  .B\r    .C\r\r.D\r",
+    ".Ä ü\n\t.Ö\n\t\tö\n",
+    ".A\n  \n.B\n",
+  ];
+  for input in inputs {
+    let expected = parse(input).unwrap();
+    assert_same_nodes(&expected, &parse_reader(input.as_bytes()).unwrap());
+    assert_same_nodes(&expected, &parse_reader(SlowReader(input.as_bytes())).unwrap());
+  }
+}
+
+#[test]
+fn _0002() {
+  // Tokens read from the stream are the same as tokens from the text.
+  let input = ".A a\n    .B\n\n    b\n    .C\n";
+  let expected = tokenize(input).unwrap().into_iter().map(Token::into_owned).collect::<Vec<Token>>();
+  let actual = StreamTokenizer::new(BufReader::new(input.as_bytes())).collect::<Result<Vec<Token>, _>>().unwrap();
+  assert_eq!(expected, actual);
+}
+
+#[test]
+fn _0003() {
+  // Errors have the same positions as errors reported for the text.
+  let inputs = ["", " .A\n", ".A\n  .B\n   .C\n", ".A\n  .B\n.C\u{1}\n", ".A\n.B\u{1}"];
+  for input in inputs {
+    assert_eq!(parse(input).unwrap_err(), parse_reader(input.as_bytes()).unwrap_err());
+  }
+}
+
+#[test]
+fn _0004() {
+  // Invalid UTF-8 sequence.
+  let input = b".A\r\n  .B \xC3\xA4\xC3\n";
+  let err = parse_reader(&input[..]).unwrap_err();
+  assert_eq!(&ErrorKind::InvalidUtf8, err.kind());
+  assert_eq!(Position::new(2, 7, 11), err.position());
+  assert_eq!("invalid UTF-8 sequence at row 2 and column 7", err.to_string());
+}
+
+#[test]
+fn _0005() {
+  // Reading error.
+  let err = parse_reader(FailingReader(b".A\n  .B\n")).unwrap_err();
+  assert!(matches!(
+    err.kind(),
+    ErrorKind::Io {
+      kind: std::io::ErrorKind::Other,
+      ..
+    }
+  ));
+  assert_eq!(Position::new(3, 1, 8), err.position());
+  assert_eq!("I/O error: broken pipe at row 3 and column 1", err.to_string());
+}
+
+#[test]
+fn _0006() {
+  // Tokens are produced before the whole input is read.
+  let input = ".A\n.B\n";
+  let mut tokens = StreamTokenizer::new(BufReader::new(FailingReader(input.as_bytes())));
+  assert!(matches!(tokens.next(), Some(Ok(Token::Indentation(indentation, _))) if indentation.is_empty()));
+  assert!(matches!(tokens.next(), Some(Ok(Token::NodeName(..)))));
+  assert!(matches!(tokens.next(), Some(Ok(Token::NodeContent(..)))));
+  // The second node can not be completed, because reading the next line fails.
+  assert!(matches!(tokens.next(), Some(Err(_))));
+  assert!(tokens.next().is_none());
+}
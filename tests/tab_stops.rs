@@ -0,0 +1,68 @@
+#[path = "common/mod.rs"]
+mod common;
+
+use common::levels;
+use idml::{parse, parse_reader_with_options, parse_with_options, ErrorKind, ParseOptions, TAB, WS};
+
+#[test]
+fn _0001() {
+  // Mixed tabs and spaces are rejected by default.
+  let input = ".A\n    .B\n\t\t\t\t.C\n";
+  assert_eq!(&ErrorKind::InconsistentIndentation, parse(input).unwrap_err().kind());
+}
+
+#[test]
+fn _0002() {
+  // Tabs and spaces are accepted with the tab stop.
+  let input = ".A\n    .B\n\t\t.C\n  \t.D\n\t    .E\n.F\n";
+  let root = parse_with_options(input, &ParseOptions::new().with_tab_stop(4)).unwrap();
+  assert_eq!(vec![("A", 1), ("B", 2), ("C", 3), ("D", 2), ("E", 3), ("F", 1)], levels(&root));
+}
+
+#[test]
+fn _0003() {
+  // Effective width depends on the tab stop.
+  let input = ".A\n        .B\n\t.C\n";
+  let root = parse_with_options(input, &ParseOptions::new().with_tab_stop(8)).unwrap();
+  let a = root.first_with_name("A").unwrap();
+  assert_eq!(vec!["B", "C"], a.children().map(|node| node.name()).collect::<Vec<&str>>());
+  let err = parse_with_options(input, &ParseOptions::new().with_tab_stop(4)).unwrap_err();
+  assert_eq!("indentation jumps from level 1 to 3 at row 2 and column 1", err.to_string());
+}
+
+#[test]
+fn _0004() {
+  // Original indentation is kept, also when tabs and spaces are mixed.
+  let input = ".A\n\t.B\n    .C\n  \t.D\n";
+  let root = parse_with_options(input, &ParseOptions::new().with_tab_stop(4)).unwrap();
+  let a = root.first_with_name("A").unwrap();
+  let indents = a.children().map(|node| (node.indent(), node.indent_char())).collect::<Vec<_>>();
+  assert_eq!(vec![(1, TAB), (4, WS), (3, WS)], indents);
+  assert_eq!(input, root.to_source());
+}
+
+#[test]
+fn _0005() {
+  // Tab stop is applied when reading from a stream.
+  let input = ".A\n\t.B\n    .C\n";
+  let root = parse_reader_with_options(input.as_bytes(), &ParseOptions::new().with_tab_stop(4)).unwrap();
+  assert_eq!(2, root.first_with_name("A").unwrap().child_count());
+}
+
+#[test]
+fn _0006() {
+  // Mixed indentation survives a round trip, also when reading from a stream.
+  let input = ".A\n  \t.B\n\t    .C\n";
+  let options = ParseOptions::new().with_tab_stop(4);
+  assert_eq!(input, parse_with_options(input, &options).unwrap().to_source());
+  assert_eq!(input, parse_reader_with_options(input.as_bytes(), &options).unwrap().to_source());
+}
+
+#[test]
+fn _0007() {
+  // Fixed indentation of tabs is measured in columns with the tab stop.
+  let input = ".A\n\t.B\n\t\t.C\n    .D\n";
+  let options = ParseOptions::new().with_indent(1, TAB).with_tab_stop(4);
+  let root = parse_with_options(input, &options).unwrap();
+  assert_eq!(vec![("A", 1), ("B", 2), ("C", 3), ("D", 2)], levels(&root));
+}
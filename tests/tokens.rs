@@ -0,0 +1,65 @@
+#[path = "common/mod.rs"]
+mod common;
+
+use common::span;
+use idml::{ErrorKind, Parser, Position, Token, Tokenizer};
+
+#[test]
+fn _0001() {
+  // Tokens are yielded one by one, with positions.
+  let input = ".A a\n  .B\n";
+  let mut tokenizer = Tokenizer::new(input);
+  assert_eq!(Token::Indentation("".into(), span(1, 1, 0, 1, 1, 0)), tokenizer.next().unwrap().unwrap());
+  assert_eq!(Token::NodeName("A".into(), '.', span(1, 1, 0, 1, 3, 2)), tokenizer.next().unwrap().unwrap());
+  assert_eq!(Token::NodeContent(" a\n".into(), span(1, 3, 2, 2, 1, 5)), tokenizer.next().unwrap().unwrap());
+  assert_eq!(Token::Indentation("  ".into(), span(2, 1, 5, 2, 3, 7)), tokenizer.next().unwrap().unwrap());
+  assert_eq!(Token::NodeName("B".into(), '.', span(2, 3, 7, 2, 5, 9)), tokenizer.next().unwrap().unwrap());
+  assert_eq!(Token::NodeContent("\n".into(), span(2, 5, 9, 3, 1, 10)), tokenizer.next().unwrap().unwrap());
+  assert!(tokenizer.next().is_none());
+}
+
+#[test]
+fn _0002() {
+  // Tokens preceding an error are yielded before the error, nothing is yielded after the error.
+  let input = ".A a\n  .B\u{1}\n.C\n";
+  let tokens = Tokenizer::new(input).collect::<Vec<_>>();
+  assert_eq!(5, tokens.len());
+  assert!(tokens[..4].iter().all(|token| token.is_ok()));
+  let err = tokens[4].as_ref().unwrap_err();
+  assert_eq!(&ErrorKind::UnexpectedCharacter('\u{1}'), err.kind());
+  assert_eq!(Position::new(2, 5, 9), err.position());
+}
+
+#[test]
+fn _0003() {
+  // Tokenizing stops at the first error, when only leading tokens are consumed.
+  let input = ".A a\n\u{1}";
+  let names = Tokenizer::new(input)
+    .take(2)
+    .map(|token| token.unwrap())
+    .filter_map(|token| match token {
+      Token::NodeName(name, _, _) => Some(name),
+      _ => None,
+    })
+    .collect::<Vec<_>>();
+  assert_eq!(vec!["A"], names);
+}
+
+#[test]
+fn _0004() {
+  // Parser accepts any iterator of tokens.
+  let input = ".A a\n  .B b\n";
+  let tokens = Tokenizer::new(input).map(|token| token.unwrap());
+  let root = Parser::new(tokens).parse().unwrap();
+  let a = root.first_with_name("A").unwrap();
+  assert_eq!("a", a.text());
+  assert_eq!("b", a.first_with_name("B").unwrap().text());
+}
+
+#[test]
+fn _0005() {
+  // Collecting the tokenizer is equivalent to tokenizing the whole input.
+  let input = ".A a\r\n  .B\n\n    b\n";
+  let collected = Tokenizer::new(input).collect::<Result<Vec<Token>, _>>().unwrap();
+  assert_eq!(Tokenizer::new(input).tokenize().unwrap(), collected);
+}
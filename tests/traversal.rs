@@ -0,0 +1,105 @@
+use idml::{parse, Node};
+
+const INPUT: &str = r#".A
+  .B
+    .C
+    .D
+  .E
+.F
+  .G
+    .H
+"#;
+
+fn names<'a>(nodes: impl Iterator<Item = &'a Node<'a>>) -> Vec<&'a str> {
+  nodes.map(|node| node.name()).collect()
+}
+
+#[test]
+fn _0001() {
+  // Depth-first and breadth-first descendants.
+  let root = parse(INPUT).unwrap();
+  assert_eq!(vec!["A", "B", "C", "D", "E", "F", "G", "H"], names(root.descendants()));
+  assert_eq!(vec!["C", "D", "B", "E", "A", "H", "G", "F"], names(root.descendants_post_order()));
+  assert_eq!(vec!["A", "F", "B", "E", "G", "C", "D", "H"], names(root.descendants_breadth_first()));
+  let a = root.first_with_name("A").unwrap();
+  assert_eq!(vec!["B", "C", "D", "E"], names(a.descendants()));
+  assert_eq!(vec!["C", "D", "B", "E"], names(a.descendants_post_order()));
+  assert_eq!(vec!["B", "E", "C", "D"], names(a.descendants_breadth_first()));
+  let h = root.descendants().last().unwrap();
+  assert_eq!(0, h.descendants().count());
+  assert_eq!(0, h.descendants_post_order().count());
+  assert_eq!(0, h.descendants_breadth_first().count());
+}
+
+#[test]
+fn _0002() {
+  // Descendants with name.
+  let root = parse(".A\n  .B 1\n  .C\n    .B 2\n.B 3\n").unwrap();
+  let texts = root.descendants_with_name("B").map(|node| node.text()).collect::<Vec<&str>>();
+  assert_eq!(vec!["1", "2", "3"], texts);
+  assert_eq!(0, root.descendants_with_name("X").count());
+}
+
+#[test]
+fn _0003() {
+  // Cursor navigation to children and siblings.
+  let root = parse(INPUT).unwrap();
+  let mut cursor = root.cursor();
+  assert_eq!(0, cursor.depth());
+  assert_eq!(None, cursor.index());
+  assert!(cursor.parent().is_none());
+  assert!(cursor.next_sibling().is_none());
+  assert!(!cursor.goto_parent());
+  assert!(!cursor.goto_next_sibling());
+  assert!(cursor.goto_last_child());
+  assert_eq!("F", cursor.node().name());
+  assert_eq!(Some(1), cursor.index());
+  assert_eq!("A", cursor.previous_sibling().unwrap().name());
+  assert!(cursor.next_sibling().is_none());
+  assert!(!cursor.goto_next_sibling());
+  assert!(cursor.goto_previous_sibling());
+  assert_eq!("A", cursor.node().name());
+  assert!(cursor.previous_sibling().is_none());
+  assert!(!cursor.goto_previous_sibling());
+  assert!(cursor.goto_child(1));
+  assert_eq!("E", cursor.node().name());
+  assert!(!cursor.goto_first_child());
+  assert!(!cursor.goto_last_child());
+  assert!(!cursor.goto_child(5));
+  assert_eq!("E", cursor.node().name());
+}
+
+#[test]
+fn _0004() {
+  // Cursor navigation to parent and ancestors.
+  let root = parse(INPUT).unwrap();
+  let mut cursor = root.cursor();
+  assert!(cursor.goto_child(1) && cursor.goto_first_child() && cursor.goto_first_child());
+  assert_eq!("H", cursor.node().name());
+  assert_eq!(3, cursor.depth());
+  assert_eq!("G", cursor.parent().unwrap().name());
+  assert_eq!(vec!["G", "F", "root"], names(cursor.ancestors()));
+  assert!(cursor.goto_parent() && cursor.goto_parent());
+  assert_eq!("F", cursor.node().name());
+  assert_eq!(vec!["root"], names(cursor.ancestors()));
+  // Cursor does not move above the node it was created for.
+  let mut cursor = root.first_with_name("A").unwrap().cursor();
+  assert!(cursor.goto_first_child());
+  assert_eq!(vec!["A"], names(cursor.ancestors()));
+  assert!(cursor.goto_parent());
+  assert!(!cursor.goto_parent());
+}
+
+#[test]
+fn _0005() {
+  // Cursor visiting all nodes in pre-order.
+  let root = parse(INPUT).unwrap();
+  let mut cursor = root.cursor();
+  let mut visited = vec![];
+  while cursor.goto_next() {
+    visited.push(format!("{}:{}", cursor.node().name(), cursor.depth()));
+  }
+  assert_eq!(vec!["A:1", "B:2", "C:3", "D:3", "E:2", "F:1", "G:2", "H:3"], visited);
+  assert_eq!("root", cursor.node().name());
+  assert!(cursor.parent().is_none());
+}
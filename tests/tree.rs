@@ -0,0 +1,125 @@
+use idml::{parse, Node, NodeId, Tree};
+
+const INPUT: &str = r#".A a
+    .B b
+        .C c
+    .D d
+.E e
+"#;
+
+fn names(tree: &Tree, ids: impl Iterator<Item = NodeId>) -> Vec<String> {
+  ids.map(|id| tree.node(id).name().to_string()).collect()
+}
+
+fn find(tree: &Tree, name: &str) -> NodeId {
+  tree.descendants(tree.root()).find(|id| tree.node(*id).name() == name).unwrap()
+}
+
+#[test]
+fn _0001() {
+  // Converting nodes into tree and back.
+  let root = parse(INPUT).unwrap().into_root();
+  let tree = Tree::from(root.clone());
+  assert_eq!(vec!["A", "B", "C", "D", "E"], names(&tree, tree.descendants(tree.root())));
+  assert_eq!(vec![1, 2, 3, 4, 5], tree.descendants(tree.root()).map(|id| id.index()).collect::<Vec<usize>>());
+  assert_eq!("root", tree.node(tree.root()).name());
+  assert_eq!(2, tree.to_node(tree.root()).child_count());
+  let b = find(&tree, "B");
+  assert_eq!("b", tree.node(b).text());
+  assert_eq!("    .B b\n        .C c\n", tree.to_node(b).to_source());
+  let node = Node::from(tree);
+  assert_eq!(INPUT, node.to_source());
+  assert_eq!(root.document(2, ' '), node.document(2, ' '));
+}
+
+#[test]
+fn _0002() {
+  // Navigating the tree.
+  let tree = Tree::from(parse(INPUT).unwrap().into_root());
+  let root = tree.root();
+  let (a, b, c, d, e) = (find(&tree, "A"), find(&tree, "B"), find(&tree, "C"), find(&tree, "D"), find(&tree, "E"));
+  assert_eq!(None, tree.parent(root));
+  assert_eq!(Some(root), tree.parent(a));
+  assert_eq!(Some(b), tree.parent(c));
+  assert_eq!(Some(a), tree.first_child(root));
+  assert_eq!(Some(e), tree.last_child(root));
+  assert_eq!(Some(d), tree.next_sibling(b));
+  assert_eq!(Some(b), tree.previous_sibling(d));
+  assert_eq!(None, tree.previous_sibling(b));
+  assert_eq!(None, tree.next_sibling(e));
+  assert_eq!(None, tree.first_child(c));
+  assert_eq!(vec!["B", "D"], names(&tree, tree.children(a)));
+  assert_eq!(vec!["B", "A", "root"], names(&tree, tree.ancestors(c)));
+  assert_eq!(vec!["B", "C", "D"], names(&tree, tree.descendants(a)));
+  assert_eq!(0, tree.descendants(d).count());
+}
+
+#[test]
+fn _0003() {
+  // Moving nodes adjusts levels and indentation.
+  let mut tree = Tree::from(parse(INPUT).unwrap().into_root());
+  let (a, b, d, e) = (find(&tree, "A"), find(&tree, "B"), find(&tree, "D"), find(&tree, "E"));
+  tree.append(e, b);
+  assert_eq!(vec!["D"], names(&tree, tree.children(a)));
+  assert_eq!(Some(e), tree.parent(b));
+  assert_eq!(None, tree.previous_sibling(d));
+  tree.insert_before(a, d);
+  tree.prepend(e, a);
+  assert_eq!(1, tree.node(d).level());
+  assert_eq!(2, tree.node(a).level());
+  assert_eq!(".D d\n.E e\n    .A a\n    .B b\n        .C c\n", Node::from(tree).to_source());
+}
+
+#[test]
+fn _0004() {
+  // Adding, detaching and editing nodes.
+  let mut tree = Tree::new();
+  let root = tree.root();
+  let mut node = Node::new('.', "A").unwrap();
+  node.push_child(Node::new('.', "B").unwrap()).unwrap();
+  let a = tree.add(node);
+  assert_eq!(None, tree.parent(a));
+  tree.append(root, a);
+  let c = tree.add(Node::new('.', "C").unwrap());
+  tree.insert_after(a, c);
+  tree.set_content(c, "c");
+  tree.set_name(a, "X").unwrap();
+  let b = tree.first_child(a).unwrap();
+  tree.append(c, b);
+  assert_eq!(".X\n.C c\n  .B\n", tree.to_node(root).to_source());
+  tree.detach(a);
+  tree.detach(a);
+  assert_eq!(None, tree.parent(a));
+  assert_eq!(vec!["C"], names(&tree, tree.children(root)));
+  assert_eq!(".C c\n  .B\n", Node::from(tree).to_source());
+}
+
+#[test]
+fn _0005() {
+  // Moving a node to its own place keeps the tree unchanged.
+  let mut tree = Tree::from(parse(INPUT).unwrap().into_root());
+  let (a, b, d) = (find(&tree, "A"), find(&tree, "B"), find(&tree, "D"));
+  tree.append(a, d);
+  tree.prepend(a, b);
+  tree.insert_after(b, d);
+  tree.insert_before(d, b);
+  assert_eq!(INPUT, Node::from(tree).to_source());
+}
+
+#[test]
+#[should_panic(expected = "node cannot be attached below itself")]
+fn _0006() {
+  let mut tree = Tree::from(parse(INPUT).unwrap().into_root());
+  let (a, c) = (find(&tree, "A"), find(&tree, "C"));
+  tree.append(c, a);
+}
+
+#[test]
+fn _0007() {
+  // Owned tree outlives the input.
+  let tree: Tree<'static> = {
+    let input = INPUT.to_string();
+    Tree::from(parse(&input).unwrap().into_root()).into_owned()
+  };
+  assert_eq!(INPUT, Node::from(tree).to_source());
+}
@@ -275,3 +275,46 @@ fn _0023() {
   assert_eq!(" node content\n", node.content());
   assert_eq!("node content", node.text());
 }
+
+#[test]
+fn _0024() {
+  // Line containing only whitespace does not hide the following node.
+  let input = ".A\n  \n.B\r\n";
+  let root = parse(input).unwrap();
+  let names = root.children().map(|node| node.name()).collect::<Vec<&str>>();
+  assert_eq!(vec!["A", "B"], names);
+  assert_eq!("\n  \n", root.first_with_name("A").unwrap().content());
+  assert_eq!(input, root.document(4, WS));
+}
+
+#[test]
+fn _0025() {
+  // Last line without a newline.
+  let input = "- node content";
+  let root = parse(input).unwrap();
+  let node = root.children().next().unwrap();
+  assert_eq!("", node.name());
+  assert_eq!(" node content", node.content());
+  assert_eq!("node content", node.text());
+  assert_eq!(input, root.to_source());
+}
+
+#[test]
+fn _0026() {
+  // Last node without content and without a newline.
+  let input = ".A\n  .B";
+  let root = parse(input).unwrap();
+  let b = root.first_with_name("A").unwrap().first_with_name("B").unwrap();
+  assert_eq!("", b.content());
+  assert_eq!(b.name_span().end(), b.content_span().start());
+  assert_eq!(input, root.document(2, WS));
+}
+
+#[test]
+fn _0027() {
+  // Last line without a newline, containing only whitespace.
+  let input = ".A a\r\n  ";
+  let root = parse(input).unwrap();
+  assert_eq!(" a\r\n  ", root.first_with_name("A").unwrap().content());
+  assert_eq!(input, root.to_source());
+}
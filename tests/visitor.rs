@@ -0,0 +1,188 @@
+use idml::{parse, Node, Visit, Visitor, VisitorMut};
+
+const INPUT: &str = r#".A
+  .B
+    .C
+  .D
+.E
+  .F
+"#;
+
+/// Records entered and left nodes, returning the configured decisions.
+#[derive(Default)]
+struct Recorder {
+  events: Vec<String>,
+  skip: Option<&'static str>,
+  stop_entering: Option<&'static str>,
+  stop_leaving: Option<&'static str>,
+}
+
+impl Recorder {
+  fn decide(&self, name: &str, skip: Option<&str>, stop: Option<&str>) -> Visit {
+    if stop == Some(name) {
+      Visit::Stop
+    } else if skip == Some(name) {
+      Visit::SkipChildren
+    } else {
+      Visit::Continue
+    }
+  }
+}
+
+impl Visitor for Recorder {
+  fn enter_node(&mut self, node: &Node) -> Visit {
+    self.events.push(format!("+{}", node.name()));
+    self.decide(node.name(), self.skip, self.stop_entering)
+  }
+
+  fn leave_node(&mut self, node: &Node) -> Visit {
+    self.events.push(format!("-{}", node.name()));
+    self.decide(node.name(), None, self.stop_leaving)
+  }
+}
+
+impl VisitorMut for Recorder {
+  fn enter_node(&mut self, node: &mut Node) -> Visit {
+    Visitor::enter_node(self, node)
+  }
+
+  fn leave_node(&mut self, node: &mut Node) -> Visit {
+    Visitor::leave_node(self, node)
+  }
+}
+
+#[test]
+fn _0001() {
+  // Nodes are entered before and left after their children.
+  let mut root = parse(INPUT).unwrap().into_root();
+  let expected = "+root +A +B +C -C -B +D -D -A +E +F -F -E -root";
+  let mut recorder = Recorder::default();
+  assert!(root.walk(&mut recorder));
+  assert_eq!(expected, recorder.events.join(" "));
+  let mut recorder = Recorder::default();
+  assert!(root.walk_mut(&mut recorder));
+  assert_eq!(expected, recorder.events.join(" "));
+  assert_eq!(INPUT, root.to_source());
+}
+
+#[test]
+fn _0002() {
+  // Skipping children.
+  let mut root = parse(INPUT).unwrap().into_root();
+  let expected = "+root +A +B -B +D -D -A +E +F -F -E -root";
+  let mut recorder = Recorder {
+    skip: Some("B"),
+    ..Default::default()
+  };
+  assert!(root.walk(&mut recorder));
+  assert_eq!(expected, recorder.events.join(" "));
+  recorder.events.clear();
+  assert!(root.walk_mut(&mut recorder));
+  assert_eq!(expected, recorder.events.join(" "));
+  assert_eq!(INPUT, root.to_source());
+  let mut recorder = Recorder {
+    skip: Some("root"),
+    ..Default::default()
+  };
+  assert!(root.walk(&mut recorder));
+  assert!(root.walk_mut(&mut recorder));
+  assert_eq!("+root -root +root -root", recorder.events.join(" "));
+}
+
+#[test]
+fn _0003() {
+  // Stopping when entering or leaving nodes, the tree stays complete.
+  let mut root = parse(INPUT).unwrap().into_root();
+  for (stop_entering, stop_leaving, expected) in [
+    (Some("C"), None, "+root +A +B +C"),
+    (None, Some("B"), "+root +A +B +C -C -B"),
+    (Some("root"), None, "+root"),
+    (None, Some("root"), "+root +A +B +C -C -B +D -D -A +E +F -F -E -root"),
+  ] {
+    let mut recorder = Recorder {
+      stop_entering,
+      stop_leaving,
+      ..Default::default()
+    };
+    assert!(!root.walk(&mut recorder));
+    assert_eq!(expected, recorder.events.join(" "));
+    recorder.events.clear();
+    assert!(!root.walk_mut(&mut recorder));
+    assert_eq!(expected, recorder.events.join(" "));
+    assert_eq!(INPUT, root.to_source());
+  }
+}
+
+#[test]
+fn _0004() {
+  // Modifying nodes while walking.
+  struct Rename;
+
+  impl VisitorMut for Rename {
+    fn enter_node(&mut self, node: &mut Node) -> Visit {
+      if node.name() == "A" {
+        node.retain(|child| child.name() != "B");
+        node.push_child(Node::new('.', "G").unwrap()).unwrap();
+      }
+      Visit::Continue
+    }
+
+    fn leave_node(&mut self, node: &mut Node) -> Visit {
+      if !node.name().starts_with("root") {
+        let name = format!("{}{}", node.name().to_lowercase(), node.child_count());
+        node.set_name(name).unwrap();
+      }
+      Visit::Continue
+    }
+  }
+
+  let mut root = parse(INPUT).unwrap().into_root();
+  assert!(root.walk_mut(&mut Rename));
+  assert_eq!(".a2\n  .d0\n  .g0\n.e1\n  .f0\n", root.to_source());
+}
+
+#[test]
+fn _0005() {
+  // Deeply nested trees are walked without recursion.
+  const DEPTH: usize = 200_000;
+  let mut root = Node::root();
+  let mut node = &mut root;
+  for _ in 0..DEPTH {
+    node.push_child(Node::new('.', "a").unwrap()).unwrap();
+    node = node.children_mut().next().unwrap();
+  }
+
+  #[derive(Default)]
+  struct Depth(usize, usize);
+
+  impl Visitor for Depth {
+    fn enter_node(&mut self, _: &Node) -> Visit {
+      self.0 += 1;
+      self.1 = self.1.max(self.0);
+      Visit::Continue
+    }
+
+    fn leave_node(&mut self, _: &Node) -> Visit {
+      self.0 -= 1;
+      Visit::Continue
+    }
+  }
+
+  impl VisitorMut for Depth {
+    fn enter_node(&mut self, node: &mut Node) -> Visit {
+      Visitor::enter_node(self, node)
+    }
+
+    fn leave_node(&mut self, node: &mut Node) -> Visit {
+      Visitor::leave_node(self, node)
+    }
+  }
+
+  let mut depth = Depth::default();
+  assert!(root.walk(&mut depth));
+  assert_eq!(DEPTH + 1, depth.1);
+  let mut depth = Depth::default();
+  assert!(root.walk_mut(&mut depth));
+  assert_eq!(DEPTH + 1, depth.1);
+  assert_eq!(DEPTH, root.descendants().count());
+}